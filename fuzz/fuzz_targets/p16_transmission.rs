@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use aoc2021_rust::puzzles::p16::P16;
+use aoc2021_rust::puzzles::puzzle::Puzzle;
+
+fuzz_target!(|transmission: &str| {
+    let raw_data = transmission.lines().map(String::from).collect();
+    let _ = P16.parse_data(&raw_data);
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use aoc2021_rust::puzzles::p8::P8;
+use aoc2021_rust::puzzles::puzzle::Puzzle;
+
+fuzz_target!(|notes: &str| {
+    let raw_data = notes.lines().map(String::from).collect();
+    let _ = P8.parse_data(&raw_data);
+});
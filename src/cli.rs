@@ -0,0 +1,121 @@
+use crate::puzzles::report::ReportOptions;
+use crate::puzzles::LATEST_DAY;
+
+pub enum Command {
+    Run {
+        day: u8,
+        test_input: bool,
+    },
+    Watch {
+        day: u8,
+        test_input: bool,
+        rebuild: bool,
+    },
+    Report {
+        day: u8,
+        test_input: bool,
+        name: String,
+        options: ReportOptions,
+    },
+    #[cfg(feature = "fetch")]
+    Fetch {
+        day: u8,
+    },
+    #[cfg(feature = "submit")]
+    Submit {
+        day: u8,
+        part: u8,
+    },
+}
+
+type ArgsParseErr = String;
+
+pub const USAGE: &str = "\
+Usage:
+    aoc2021-rust [run] [--day N] [--test]
+    aoc2021-rust watch --day N [--test] [--rebuild]
+    aoc2021-rust report --day N [--test] NAME [key=value ...]
+    aoc2021-rust fetch --day N
+    aoc2021-rust submit --day N --part P";
+
+pub fn parse_args<I: Iterator<Item = String>>(args: I) -> Result<Command, ArgsParseErr> {
+    let mut args = args.peekable();
+    let command = match args.peek().map(String::as_str) {
+        Some(name) if !name.starts_with("--") => args.next(),
+        _ => None,
+    };
+
+    let mut day = None;
+    #[cfg(feature = "submit")]
+    let mut part = None;
+    let mut test_input = false;
+    let mut rebuild = false;
+    let mut positional = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--day" => day = Some(parse_day(args.next())?),
+            #[cfg(feature = "submit")]
+            "--part" => part = Some(parse_part(args.next())?),
+            "--test" => test_input = true,
+            "--rebuild" => rebuild = true,
+            _ if !arg.starts_with("--") => positional.push(arg),
+            _ => return Err(format!("Unknown argument: `{}`", arg)),
+        }
+    }
+
+    if !positional.is_empty() && command.as_deref() != Some("report") {
+        return Err(format!("Unknown argument: `{}`", positional[0]));
+    }
+
+    match command.as_deref() {
+        None | Some("run") => Ok(Command::Run {
+            day: day.unwrap_or(LATEST_DAY),
+            test_input,
+        }),
+        Some("watch") => day
+            .map(|day| Command::Watch {
+                day,
+                test_input,
+                rebuild,
+            })
+            .ok_or(String::from("watch needs a --day")),
+        Some("report") => {
+            let (name, options) = positional
+                .split_first()
+                .ok_or(String::from("report needs a report name"))?;
+            Ok(Command::Report {
+                day: day.ok_or(String::from("report needs a --day"))?,
+                test_input,
+                name: name.clone(),
+                options: ReportOptions::parse(options)?,
+            })
+        }
+        #[cfg(feature = "fetch")]
+        Some("fetch") => day
+            .map(|day| Command::Fetch { day })
+            .ok_or(String::from("fetch needs a --day")),
+        #[cfg(feature = "submit")]
+        Some("submit") => day
+            .zip(part)
+            .map(|(day, part)| Command::Submit { day, part })
+            .ok_or(String::from("submit needs a --day and a --part")),
+        Some(other) => Err(format!("Unknown command: `{}`", other)),
+    }
+}
+
+fn parse_day(value: Option<String>) -> Result<u8, ArgsParseErr> {
+    value
+        .as_ref()
+        .and_then(|day| day.parse::<u8>().ok())
+        .filter(|day| (1..=25).contains(day))
+        .ok_or(format!("Invalid day: `{}`", value.unwrap_or_default()))
+}
+
+#[cfg(feature = "submit")]
+fn parse_part(value: Option<String>) -> Result<u8, ArgsParseErr> {
+    value
+        .as_ref()
+        .and_then(|part| part.parse::<u8>().ok())
+        .filter(|part| (1..=2).contains(part))
+        .ok_or(format!("Invalid part: `{}`", value.unwrap_or_default()))
+}
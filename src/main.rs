@@ -1,9 +1,60 @@
-mod puzzles;
-mod utils;
+mod cli;
+mod watch;
 
-use crate::puzzles::p17::P17;
-use crate::puzzles::puzzle::Puzzle;
+use std::env;
+use std::process;
+
+use aoc2021_rust::{puzzles, utils};
+
+use crate::cli::Command;
+#[cfg(feature = "submit")]
+use crate::utils::submit;
+#[cfg(feature = "fetch")]
+use crate::utils::{fetch, session::Session};
+
+fn run(command: Command) -> Result<(), String> {
+    match command {
+        Command::Run { day, test_input } => puzzles::solve(day, test_input),
+        Command::Watch {
+            day,
+            test_input,
+            rebuild,
+        } => watch::watch(day, test_input, rebuild),
+        Command::Report {
+            day,
+            test_input,
+            name,
+            options,
+        } => {
+            let report = puzzles::report(day, test_input, &name, &options)?;
+            println!("{}", report);
+            Ok(())
+        }
+        #[cfg(feature = "fetch")]
+        Command::Fetch { day } => {
+            let session = Session::load()?;
+            let path = fetch::fetch_input(day, &session)?;
+            println!("{}", path.display());
+            Ok(())
+        }
+        #[cfg(feature = "submit")]
+        Command::Submit { day, part } => {
+            let answer = puzzles::answer(day, false, part)?;
+            println!("Submitting {} for puzzle {} part {}", answer, day, part);
+            let outcome = submit::submit(day, part, &answer)?;
+            println!("{}", outcome);
+            Ok(())
+        }
+    }
+}
 
 fn main() {
-    Puzzle::solve(&P17 {}, false)
+    let result = cli::parse_args(env::args().skip(1))
+        .map_err(|err| format!("{}\n\n{}", err, cli::USAGE))
+        .and_then(run);
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
 }
@@ -1,4 +1,5 @@
 pub mod puzzle;
+pub mod report;
 
 pub mod p1;
 pub mod p2;
@@ -17,3 +18,108 @@ pub mod p14;
 pub mod p15;
 pub mod p16;
 pub mod p17;
+
+use crate::puzzles::puzzle::{Answers, Puzzle};
+use crate::puzzles::report::ReportOptions;
+
+pub const LATEST_DAY: u8 = 17;
+
+macro_rules! with_puzzle {
+    ($number:expr, $puzzle:ident => $body:expr) => {
+        match $number {
+            1 => {
+                let $puzzle = p1::P1;
+                $body
+            }
+            2 => {
+                let $puzzle = p2::P2;
+                $body
+            }
+            3 => {
+                let $puzzle = p3::P3;
+                $body
+            }
+            4 => {
+                let $puzzle = p4::P4;
+                $body
+            }
+            5 => {
+                let $puzzle = p5::P5;
+                $body
+            }
+            6 => {
+                let $puzzle = p6::P6;
+                $body
+            }
+            7 => {
+                let $puzzle = p7::P7;
+                $body
+            }
+            8 => {
+                let $puzzle = p8::P8;
+                $body
+            }
+            9 => {
+                let $puzzle = p9::P9;
+                $body
+            }
+            10 => {
+                let $puzzle = p10::P10;
+                $body
+            }
+            11 => {
+                let $puzzle = p11::P11;
+                $body
+            }
+            12 => {
+                let $puzzle = p12::P12;
+                $body
+            }
+            13 => {
+                let $puzzle = p13::P13;
+                $body
+            }
+            14 => {
+                let $puzzle = p14::P14;
+                $body
+            }
+            15 => {
+                let $puzzle = p15::P15;
+                $body
+            }
+            16 => {
+                let $puzzle = p16::P16;
+                $body
+            }
+            17 => {
+                let $puzzle = p17::P17;
+                $body
+            }
+            _ => Err(format!("No solution for puzzle {}", $number)),
+        }
+    };
+}
+
+pub fn answers(number: u8, test_input: bool) -> Result<Answers, String> {
+    with_puzzle!(number, puzzle => puzzle.answers(test_input))
+}
+
+pub fn answer(number: u8, test_input: bool, part: u8) -> Result<String, String> {
+    with_puzzle!(number, puzzle => puzzle.answer(test_input, part))
+}
+
+pub fn solve(number: u8, test_input: bool) -> Result<(), String> {
+    let answers = answers(number, test_input)?;
+    println!("{}", answers.part_one);
+    println!("{}", answers.part_two);
+    Ok(())
+}
+
+pub fn report(
+    number: u8,
+    test_input: bool,
+    name: &str,
+    options: &ReportOptions,
+) -> Result<String, String> {
+    with_puzzle!(number, puzzle => puzzle.run_report(test_input, name, options))
+}
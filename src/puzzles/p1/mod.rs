@@ -1,57 +1,306 @@
+use std::collections::VecDeque;
+
 use crate::puzzles::puzzle::Puzzle;
+use crate::puzzles::report::ReportOptions;
 use crate::utils::input_file;
 
-fn count_increases(depths: &Vec<u16>) -> u16 {
-    let num_depths = depths.len();
-    let mut increases = 0u16;
+pub struct WindowStats {
+    sum: u64,
+    mean: f64,
+    min: u32,
+    max: u32,
+}
+
+#[derive(Default)]
+pub struct Changes {
+    increases: usize,
+    decreases: usize,
+    plateaus: usize,
+    longest_increasing_run: usize,
+}
+
+pub struct DepthAnalysis {
+    window_size: usize,
+    windows: Vec<WindowStats>,
+    changes: Changes,
+}
+
+impl WindowStats {
+    pub fn sum(&self) -> u64 {
+        self.sum
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    pub fn min(&self) -> u32 {
+        self.min
+    }
+
+    pub fn max(&self) -> u32 {
+        self.max
+    }
+}
+
+// Changes between consecutive window sums
+impl Changes {
+    pub fn increases(&self) -> usize {
+        self.increases
+    }
+
+    pub fn decreases(&self) -> usize {
+        self.decreases
+    }
+
+    pub fn plateaus(&self) -> usize {
+        self.plateaus
+    }
+
+    pub fn longest_increasing_run(&self) -> usize {
+        self.longest_increasing_run
+    }
+}
+
+impl DepthAnalysis {
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    // One per full window, in order; empty when there are fewer depths than the window size
+    pub fn windows(&self) -> &[WindowStats] {
+        &self.windows
+    }
+
+    pub fn changes(&self) -> &Changes {
+        &self.changes
+    }
+}
+
+// Running sum plus monotonic queues of indices for the min and max,
+// so each depth enters and leaves every structure once whatever the window size
+fn build_window_stats(depths: &[u32], window_size: usize) -> Vec<WindowStats> {
+    if window_size == 0 || depths.len() < window_size {
+        return Vec::new();
+    }
+
+    let mut windows = Vec::with_capacity(depths.len() - window_size + 1);
+    let mut sum = 0u64;
+    let mut mins: VecDeque<usize> = VecDeque::new();
+    let mut maxes: VecDeque<usize> = VecDeque::new();
+
+    for (index, &depth) in depths.iter().enumerate() {
+        sum += u64::from(depth);
+        if index >= window_size {
+            sum -= u64::from(depths[index - window_size]);
+        }
+
+        while mins.back().is_some_and(|&back| depths[back] >= depth) {
+            mins.pop_back();
+        }
+        mins.push_back(index);
+
+        while maxes.back().is_some_and(|&back| depths[back] <= depth) {
+            maxes.pop_back();
+        }
+        maxes.push_back(index);
+
+        if index + 1 < window_size {
+            continue;
+        }
+
+        let window_start = index + 1 - window_size;
+        while mins.front().is_some_and(|&front| front < window_start) {
+            mins.pop_front();
+        }
+        while maxes.front().is_some_and(|&front| front < window_start) {
+            maxes.pop_front();
+        }
+
+        windows.push(WindowStats {
+            sum,
+            mean: sum as f64 / window_size as f64,
+            min: depths[mins[0]],
+            max: depths[maxes[0]],
+        });
+    }
 
-    for index in 0..(num_depths - 1) {
-        let next_index = index + 1;
+    windows
+}
 
-        let current = depths[index];
-        let next = depths[next_index];
+fn count_changes(sums: &[u64]) -> Changes {
+    let mut changes = Changes::default();
+    let mut increasing_run = 0;
 
+    for pair in sums.windows(2) {
+        let (current, next) = (pair[0], pair[1]);
         if next > current {
-            increases += 1;
+            changes.increases += 1;
+            increasing_run += 1;
+            changes.longest_increasing_run = changes.longest_increasing_run.max(increasing_run);
+        } else {
+            if next < current {
+                changes.decreases += 1;
+            } else {
+                changes.plateaus += 1;
+            }
+            increasing_run = 0;
+        }
+    }
+
+    changes
+}
+
+// Stats for every `window_size` run of depths and how their sums change, in one pass
+pub fn analyze_depths(depths: &[u32], window_size: usize) -> DepthAnalysis {
+    let windows = build_window_stats(depths, window_size);
+    let sums = windows
+        .iter()
+        .map(|window| window.sum)
+        .collect::<Vec<u64>>();
+    let changes = count_changes(&sums);
+
+    DepthAnalysis {
+        window_size,
+        windows,
+        changes,
+    }
+}
+
+fn format_window_report(analysis: &DepthAnalysis) -> String {
+    let changes = &analysis.changes;
+    let mut report = format!(
+        "window size: {}\nwindows: {}\nincreases: {}\ndecreases: {}\nplateaus: {}\nlongest increasing run: {}\n",
+        analysis.window_size,
+        analysis.windows.len(),
+        changes.increases,
+        changes.decreases,
+        changes.plateaus,
+        changes.longest_increasing_run
+    );
+
+    report += "window,sum,mean,min,max";
+    for (index, window) in analysis.windows.iter().enumerate() {
+        report += &format!(
+            "\n{},{},{:.2},{},{}",
+            index, window.sum, window.mean, window.min, window.max
+        );
+    }
+
+    report
+}
+
+pub enum Extremum {
+    Min,
+    Max,
+}
+
+// Strict local extrema; the first and last depths have only one neighbour so never qualify
+fn find_extrema(depths: &[u32]) -> Vec<Option<Extremum>> {
+    let mut extrema = depths.iter().map(|_| None).collect::<Vec<_>>();
+    for (index, triple) in depths.windows(3).enumerate() {
+        let (prev, current, next) = (triple[0], triple[1], triple[2]);
+        if current < prev && current < next {
+            extrema[index + 1] = Some(Extremum::Min);
+        } else if current > prev && current > next {
+            extrema[index + 1] = Some(Extremum::Max);
         }
     }
 
-    increases
+    extrema
 }
 
-fn build_list_of_summed_windows(depths: &Vec<u16>) -> Vec<u16> {
-    let num_depths = depths.len();
-    let mut window_start = 0;
-    let mut window_end = 2;
-    let mut summed_windows: Vec<u16> = Vec::new();
+// Change from the previous depth, kept only when it's at least the threshold either way
+fn find_jumps(depths: &[u32], threshold: u32) -> Vec<Option<i64>> {
+    let mut jumps = vec![None];
+    jumps.extend(depths.windows(2).map(|pair| {
+        let delta = i64::from(pair[1]) - i64::from(pair[0]);
+        if delta.unsigned_abs() >= u64::from(threshold) {
+            Some(delta)
+        } else {
+            None
+        }
+    }));
+    jumps.truncate(depths.len());
+    jumps
+}
 
-    for _index in 0..(num_depths - 2) {
-        let sum = &depths[window_start..=window_end].iter().sum();
-        summed_windows.push(*sum);
+// One CSV row per depth; moving averages trail, so they're blank until a window fills
+fn format_trend_report(depths: &[u32], widths: &[usize], threshold: u32) -> String {
+    let averages = widths
+        .iter()
+        .map(|&width| build_window_stats(depths, width))
+        .collect::<Vec<_>>();
+    let jumps = find_jumps(depths, threshold);
+    let extrema = find_extrema(depths);
 
-        window_start += 1;
-        window_end += 1;
+    let average_headers = widths.iter().map(|width| format!(",ma{}", width));
+    let mut report = String::from("index,depth");
+    report.extend(average_headers);
+    report += ",jump,extremum";
+
+    for (index, depth) in depths.iter().enumerate() {
+        report += &format!("\n{},{}", index, depth);
+        for (width, windows) in widths.iter().zip(averages.iter()) {
+            let window = (index + 1)
+                .checked_sub(*width)
+                .and_then(|start| windows.get(start));
+            report += &window.map_or(String::from(","), |w| format!(",{:.2}", w.mean));
+        }
+
+        let jump = jumps[index].map_or(String::new(), |delta| delta.to_string());
+        let extremum = match extrema[index] {
+            Some(Extremum::Min) => "min",
+            Some(Extremum::Max) => "max",
+            None => "",
+        };
+        report += &format!(",{},{}", jump, extremum);
     }
 
-    summed_windows
+    report
 }
 
 pub struct P1;
-impl Puzzle<Vec<u16>> for P1 {
+impl Puzzle<Vec<u32>> for P1 {
     fn number(&self) -> u8 {
         1
     }
 
-    fn parse_data(&self, raw_data: &Vec<String>) -> Vec<u16> {
-        raw_data.iter().map(input_file::as_unsigned_short).collect()
+    fn parse_data(&self, raw_data: &Vec<String>) -> Result<Vec<u32>, String> {
+        raw_data.iter().map(input_file::as_unsigned_int).collect()
+    }
+
+    fn solve_part_one(&self, depths: &Vec<u32>) -> Result<String, String> {
+        Ok(analyze_depths(depths, 1).changes.increases.to_string())
     }
 
-    fn solve_part_one(&self, depths: &Vec<u16>) {
-        println!("{}", count_increases(depths))
+    fn solve_part_two(&self, depths: &Vec<u32>) -> Result<String, String> {
+        Ok(analyze_depths(depths, 3).changes.increases.to_string())
     }
 
-    fn solve_part_two(&self, depths: &Vec<u16>) {
-        let summed_windows = build_list_of_summed_windows(depths);
-        println!("{}", count_increases(&summed_windows))
+    fn report(
+        &self,
+        depths: &Vec<u32>,
+        name: &str,
+        options: &ReportOptions,
+    ) -> Result<String, String> {
+        match name {
+            "windows" => {
+                let window_size = options.get("size", 3usize)?;
+                if window_size == 0 {
+                    return Err(String::from("Window size must be at least 1"));
+                }
+                Ok(format_window_report(&analyze_depths(depths, window_size)))
+            }
+            "trend" => {
+                let widths = options.get_list("widths", vec![3usize, 5, 10])?;
+                if widths.contains(&0) {
+                    return Err(String::from("Moving average widths must be at least 1"));
+                }
+                let threshold = options.get("threshold", 50u32)?;
+                Ok(format_trend_report(depths, &widths, threshold))
+            }
+            _ => Err(format!("Puzzle 1 has no `{}` report", name)),
+        }
     }
 }
@@ -1,122 +1,387 @@
+use std::fmt;
+
 use crate::puzzles::puzzle::Puzzle;
+use crate::puzzles::report::ReportOptions;
 
 type Program = Vec<String>;
 
-fn find_first_incorrect_close_chr(line: &String) -> Option<char> {
-    let mut stack = Vec::new();
-    line.chars().find(|&chr| {
-        if is_open(chr) {
-            stack.push(chr);
-            false
-        } else {
-            let top = stack[stack.len() - 1];
-            if top == matching_open(chr) {
-                stack.pop();
-                false
-            } else {
-                true
-            }
-        }
-    })
+// A delimiter pair and what its closer is worth when found illegally (part one) or
+// when it's needed to complete a line (part two)
+pub struct DelimiterPair {
+    open: char,
+    close: char,
+    corrupt_score: u64,
+    completion_score: u64,
 }
 
-fn is_open(chr: char) -> bool {
-    chr == '{' || chr == '(' || chr == '[' || chr == '<'
+pub struct Syntax {
+    pairs: Vec<DelimiterPair>,
+    completion_multiplier: u64,
 }
 
-fn matching_open(chr: char) -> char {
-    match chr {
-        '}' => '{',
-        ')' => '(',
-        ']' => '[',
-        '>' => '<',
-        _ => chr,
-    }
+// Columns count from 1
+pub enum Diagnostic {
+    Valid,
+    Corrupted {
+        column: usize,
+        expected: char,
+        found: char,
+    },
+    UnexpectedCloser {
+        column: usize,
+        found: char,
+    },
+    UnknownCharacter {
+        column: usize,
+        found: char,
+    },
+    Incomplete {
+        completion: String,
+    },
 }
 
-fn matching_closed(chr: char) -> char {
-    match chr {
-        '{' => '}',
-        '(' => ')',
-        '[' => ']',
-        '<' => '>',
-        _ => chr,
-    }
+// How the cheapest repair of line[start..end] treats line[start]
+#[derive(Clone, Copy)]
+enum Repair {
+    Empty,
+    Delete,
+    // Insert the character's partner: a closer after the rest of the span, or an opener just before
+    Partner,
+    // Pair with the character at this index, substituting either side if needed
+    PairWith(usize),
+}
+
+pub struct RepairedLine {
+    line: String,
+    edits: usize,
 }
 
-fn score(maybe_chr: Option<char>) -> u32 {
-    if let Some(chr) = maybe_chr {
-        match chr {
-            ')' => 3,
-            ']' => 57,
-            '}' => 1197,
-            '>' => 25137,
-            _ => 0,
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Diagnostic::Valid => write!(f, "valid"),
+            Diagnostic::Corrupted {
+                column,
+                expected,
+                found,
+            } => write!(
+                f,
+                "corrupted at column {}: expected `{}`, found `{}`",
+                column, expected, found
+            ),
+            Diagnostic::UnexpectedCloser { column, found } => {
+                write!(
+                    f,
+                    "unexpected `{}` at column {} with nothing open",
+                    found, column
+                )
+            }
+            Diagnostic::UnknownCharacter { column, found } => {
+                write!(f, "unknown character `{}` at column {}", found, column)
+            }
+            Diagnostic::Incomplete { completion } => {
+                write!(f, "incomplete, complete with `{}`", completion)
+            }
         }
-    } else {
-        0
     }
 }
 
-fn close_score(chr: char) -> u64 {
-    match chr {
-        ')' => 1,
-        ']' => 2,
-        '}' => 3,
-        '>' => 4,
-        _ => 0,
+impl Syntax {
+    pub fn new(pairs: Vec<DelimiterPair>, completion_multiplier: u64) -> Self {
+        Self {
+            pairs,
+            completion_multiplier,
+        }
     }
-}
 
-fn score_program(program: &Program) -> u32 {
-    program
-        .iter()
-        .map(|line| score(find_first_incorrect_close_chr(&line)))
-        .sum()
-}
+    // The navigation subsystem's brackets and scoring tables
+    pub fn navigation() -> Self {
+        let pair = |open, close, corrupt_score, completion_score| DelimiterPair {
+            open,
+            close,
+            corrupt_score,
+            completion_score,
+        };
+
+        Syntax::new(
+            vec![
+                pair('(', ')', 3, 1),
+                pair('[', ']', 57, 2),
+                pair('{', '}', 1197, 3),
+                pair('<', '>', 25137, 4),
+            ],
+            5,
+        )
+    }
+
+    fn opened_by(&self, chr: char) -> Option<&DelimiterPair> {
+        self.pairs.iter().find(|pair| pair.open == chr)
+    }
 
-fn get_completion_str(line: &String) -> Vec<char> {
-    let mut stack = Vec::new();
-    let mut closing_chars = Vec::new();
-
-    for chr in line.chars() {
-        if is_open(chr) {
-            stack.push(chr);
-        } else {
-            let top = stack[stack.len() - 1];
-            if top == matching_open(chr) {
-                stack.pop();
+    fn closed_by(&self, chr: char) -> Option<&DelimiterPair> {
+        self.pairs.iter().find(|pair| pair.close == chr)
+    }
+
+    // Returns the still-open pairs, innermost last, or the first problem found
+    fn walk<'a>(&'a self, line: &str) -> Result<Vec<&'a DelimiterPair>, Diagnostic> {
+        let mut stack: Vec<&DelimiterPair> = Vec::new();
+        for (index, chr) in line.chars().enumerate() {
+            let column = index + 1;
+            if let Some(pair) = self.opened_by(chr) {
+                stack.push(pair);
+            } else if self.closed_by(chr).is_some() {
+                match stack.pop() {
+                    Some(top) if top.close == chr => {}
+                    Some(top) => {
+                        return Err(Diagnostic::Corrupted {
+                            column,
+                            expected: top.close,
+                            found: chr,
+                        })
+                    }
+                    None => return Err(Diagnostic::UnexpectedCloser { column, found: chr }),
+                }
+            } else {
+                return Err(Diagnostic::UnknownCharacter { column, found: chr });
             }
         }
+
+        Ok(stack)
+    }
+
+    pub fn check(&self, line: &str) -> Diagnostic {
+        match self.walk(line) {
+            Ok(stack) if stack.is_empty() => Diagnostic::Valid,
+            Ok(stack) => Diagnostic::Incomplete {
+                completion: stack.iter().rev().map(|pair| pair.close).collect(),
+            },
+            Err(diagnostic) => diagnostic,
+        }
     }
 
-    while !stack.is_empty() {
-        let top = stack[stack.len() - 1];
-        let as_closed = matching_closed(top);
-        closing_chars.push(as_closed);
-        stack.pop();
+    // The characters a pair of delimiters becomes once matched, and how many substitutions
+    // that takes. Whichever side already fits is kept, as in the diagnostics where a mismatched
+    // closer is swapped for the one expected, and characters outside the syntax are substituted
+    // like any other.
+    fn pair_repair(&self, left: char, right: char) -> Option<(usize, char, char)> {
+        if let Some(pair) = self.opened_by(left) {
+            return Some((usize::from(right != pair.close), left, pair.close));
+        }
+        if let Some(pair) = self.closed_by(right) {
+            return Some((1, pair.open, right));
+        }
+
+        let pair = self.closed_by(left).or(self.pairs.first())?;
+        Some((2, pair.open, pair.close))
     }
 
-    closing_chars
+    // Interval DP over every span of the line for the fewest insertions, deletions and
+    // substitutions leaving it balanced. Cubic in the line length, which is fine for lines
+    // this size. Ties prefer pairing existing characters, then inserting, then deleting.
+    pub fn repair(&self, line: &str) -> RepairedLine {
+        let chars = line.chars().collect::<Vec<char>>();
+        let n = chars.len();
+        let index = |start: usize, end: usize| start * (n + 1) + end;
+
+        let mut costs = vec![0usize; (n + 1) * (n + 1)];
+        let mut repairs = vec![Repair::Empty; (n + 1) * (n + 1)];
+        for length in 1..=n {
+            for start in 0..=(n - length) {
+                let end = start + length;
+                let mut best = (1 + costs[index(start + 1, end)], Repair::Delete);
+
+                let chr = chars[start];
+                if self.opened_by(chr).is_some() || self.closed_by(chr).is_some() {
+                    best = (1 + costs[index(start + 1, end)], Repair::Partner);
+                }
+
+                for partner in (start + 1)..end {
+                    if let Some((cost, _, _)) = self.pair_repair(chr, chars[partner]) {
+                        let inside = costs[index(start + 1, partner)];
+                        let after = costs[index(partner + 1, end)];
+                        let cost = cost + inside + after;
+                        let pairing_wins = !matches!(best.1, Repair::PairWith(_));
+                        if cost < best.0 || cost == best.0 && pairing_wins {
+                            best = (cost, Repair::PairWith(partner));
+                        }
+                    }
+                }
+
+                costs[index(start, end)] = best.0;
+                repairs[index(start, end)] = best.1;
+            }
+        }
+
+        let mut repaired = String::with_capacity(n);
+        self.rebuild(&chars, &repairs, (0, n), &mut repaired);
+        RepairedLine {
+            line: repaired,
+            edits: costs[index(0, n)],
+        }
+    }
+
+    fn rebuild(
+        &self,
+        chars: &[char],
+        repairs: &[Repair],
+        (start, end): (usize, usize),
+        out: &mut String,
+    ) {
+        let n = chars.len();
+        match repairs[start * (n + 1) + end] {
+            Repair::Empty => {}
+            Repair::Delete => self.rebuild(chars, repairs, (start + 1, end), out),
+            Repair::Partner => match self.opened_by(chars[start]) {
+                Some(pair) => {
+                    out.push(pair.open);
+                    self.rebuild(chars, repairs, (start + 1, end), out);
+                    out.push(pair.close);
+                }
+                None => {
+                    let pair = self.closed_by(chars[start]).unwrap();
+                    out.push(pair.open);
+                    out.push(pair.close);
+                    self.rebuild(chars, repairs, (start + 1, end), out);
+                }
+            },
+            Repair::PairWith(partner) => {
+                let (_, left, right) = self.pair_repair(chars[start], chars[partner]).unwrap();
+                out.push(left);
+                self.rebuild(chars, repairs, (start + 1, partner), out);
+                out.push(right);
+                self.rebuild(chars, repairs, (partner + 1, end), out);
+            }
+        }
+    }
+
+    // Part one scores illegal closers, part two the closers needed to finish a line. Long
+    // completions or a large multiplier can overflow, which is an error rather than no score.
+    pub fn score(&self, diagnostic: &Diagnostic) -> Result<Option<u64>, String> {
+        let corrupt_score = |chr| self.closed_by(chr).map(|pair| pair.corrupt_score);
+        match diagnostic {
+            Diagnostic::Corrupted { found, .. } => Ok(corrupt_score(*found)),
+            Diagnostic::UnexpectedCloser { found, .. } => Ok(corrupt_score(*found)),
+            Diagnostic::Incomplete { completion } => {
+                let mut score = 0u64;
+                for chr in completion.chars() {
+                    let Some(pair) = self.closed_by(chr) else {
+                        return Ok(None);
+                    };
+                    score = score
+                        .checked_mul(self.completion_multiplier)
+                        .and_then(|score| score.checked_add(pair.completion_score))
+                        .ok_or(String::from("Completion score overflows a u64"))?;
+                }
+                Ok(Some(score))
+            }
+            Diagnostic::Valid | Diagnostic::UnknownCharacter { .. } => Ok(None),
+        }
+    }
 }
 
-fn score_completion_str(completion_str: &Vec<char>) -> u64 {
-    completion_str
+fn score_program(syntax: &Syntax, program: &Program) -> Result<u64, String> {
+    program
         .iter()
-        .fold(0, |score, &chr| (score * 5) + close_score(chr))
+        .map(|line| syntax.check(line))
+        .filter(|diagnostic| {
+            matches!(
+                diagnostic,
+                Diagnostic::Corrupted { .. } | Diagnostic::UnexpectedCloser { .. }
+            )
+        })
+        .filter_map(|diagnostic| syntax.score(&diagnostic).transpose())
+        .try_fold(0u64, |total, score| {
+            total
+                .checked_add(score?)
+                .ok_or(String::from("Syntax error score overflows a u64"))
+        })
 }
 
-fn middle_score(program: &Program) -> u64 {
+fn middle_score(syntax: &Syntax, program: &Program) -> Result<Option<u64>, String> {
     let mut scores = program
         .iter()
-        .filter(|line| find_first_incorrect_close_chr(*line).is_none())
-        .map(|line| score_completion_str(&get_completion_str(line)))
-        .collect::<Vec<u64>>();
+        .map(|line| syntax.check(line))
+        .filter(|diagnostic| matches!(diagnostic, Diagnostic::Incomplete { .. }))
+        .filter_map(|diagnostic| syntax.score(&diagnostic).transpose())
+        .collect::<Result<Vec<u64>, _>>()?;
+
+    scores.sort_unstable();
+    Ok(scores.get(scores.len() / 2).copied())
+}
+
+// pairs=()[] lists open/close characters in turn; each pair's scores default to 1 for an
+// illegal closer and its position (from 1) when completing a line
+fn syntax_from_options(options: &ReportOptions) -> Result<Syntax, String> {
+    let Some(pairs) = options.get_optional::<String>("pairs")? else {
+        return Ok(Syntax::navigation());
+    };
+
+    let chars = pairs.chars().collect::<Vec<char>>();
+    if chars.is_empty() || chars.len() % 2 != 0 {
+        return Err(String::from(
+            "Pairs look like ()[]{}, an opener then its closer",
+        ));
+    }
+
+    let count = chars.len() / 2;
+    let corrupt_scores = options.get_list("corrupt_scores", vec![1u64; count])?;
+    let completion_scores = options.get_list("completion_scores", (1..=count as u64).collect())?;
+    if corrupt_scores.len() != count || completion_scores.len() != count {
+        return Err(format!("Expected {} scores for each table", count));
+    }
+
+    let pairs = chars
+        .chunks(2)
+        .zip(corrupt_scores.into_iter().zip(completion_scores))
+        .map(|(pair, (corrupt_score, completion_score))| DelimiterPair {
+            open: pair[0],
+            close: pair[1],
+            corrupt_score,
+            completion_score,
+        })
+        .collect();
+
+    Ok(Syntax::new(pairs, options.get("multiplier", 5u64)?))
+}
+
+fn format_diagnostics_report(syntax: &Syntax, program: &Program) -> String {
+    program
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            let diagnostic = syntax.check(line);
+            let score = match syntax.score(&diagnostic) {
+                Ok(Some(score)) => format!(" (score {})", score),
+                Ok(None) => String::new(),
+                Err(err) => format!(" ({})", err),
+            };
+            format!("line {}: {}{}", index + 1, diagnostic, score)
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
 
-    scores.sort();
+fn format_repair_report(syntax: &Syntax, program: &Program) -> String {
+    let repairs = program
+        .iter()
+        .map(|line| syntax.repair(line))
+        .collect::<Vec<_>>();
+    let mut report = repairs
+        .iter()
+        .enumerate()
+        .map(|(index, repaired)| {
+            format!(
+                "line {}: {} (edits: {})",
+                index + 1,
+                repaired.line,
+                repaired.edits
+            )
+        })
+        .collect::<Vec<String>>();
 
-    let middle_index = scores.len() / 2;
-    scores[middle_index]
+    let total_edits = repairs.iter().map(|repaired| repaired.edits).sum::<usize>();
+    report.push(format!("total edits: {}", total_edits));
+    report.join("\n")
 }
 
 pub struct P10;
@@ -125,17 +390,69 @@ impl Puzzle<Program> for P10 {
         10
     }
 
-    fn parse_data(&self, raw_data: &Vec<String>) -> Program {
-        raw_data.clone()
+    fn parse_data(&self, raw_data: &Vec<String>) -> Result<Program, String> {
+        Ok(raw_data.clone())
+    }
+
+    fn solve_part_one(&self, program: &Program) -> Result<String, String> {
+        let score = score_program(&Syntax::navigation(), program)?;
+        Ok(score.to_string())
     }
 
-    fn solve_part_one(&self, program: &Program) {
-        let score = score_program(program);
-        println!("{}", score);
+    fn solve_part_two(&self, program: &Program) -> Result<String, String> {
+        let score = middle_score(&Syntax::navigation(), program)?;
+        score
+            .map(|score| score.to_string())
+            .ok_or(String::from("No incomplete lines to score"))
     }
 
-    fn solve_part_two(&self, program: &Program) {
-        let score = middle_score(program);
-        println!("{}", score);
+    fn report(
+        &self,
+        program: &Program,
+        name: &str,
+        options: &ReportOptions,
+    ) -> Result<String, String> {
+        let syntax = syntax_from_options(options)?;
+
+        match name {
+            "diagnostics" => Ok(format_diagnostics_report(&syntax, program)),
+            "repair" => Ok(format_repair_report(&syntax, program)),
+            _ => Err(format!("Puzzle 10 has no `{}` report", name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edits(line: &str) -> usize {
+        Syntax::navigation().repair(line).edits
+    }
+
+    #[test]
+    fn repair_counts_minimal_edits() {
+        assert_eq!(edits(""), 0);
+        assert_eq!(edits("([]{})"), 0);
+        assert_eq!(edits("(]"), 1);
+        assert_eq!(edits("("), 1);
+        assert_eq!(edits(")("), 2);
+        assert_eq!(edits("(x"), 1);
+        assert_eq!(edits("x)"), 1);
+        assert_eq!(edits("xy"), 2);
+    }
+
+    #[test]
+    fn repaired_lines_are_balanced() {
+        let syntax = Syntax::navigation();
+        for line in ["(]", ")(", "(x", "x", "[({(<(())[]>[[{[]{<()<>>", "))((<"] {
+            let repaired = syntax.repair(line);
+            assert!(
+                matches!(syntax.check(&repaired.line), Diagnostic::Valid),
+                "`{}` repaired to `{}`",
+                line,
+                repaired.line
+            );
+        }
     }
 }
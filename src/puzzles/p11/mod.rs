@@ -138,20 +138,29 @@ impl Puzzle<EnergyMap> for P11 {
         11
     }
 
-    fn parse_data(&self, raw_data: &Vec<String>) -> EnergyMap {
-        raw_data
+    fn parse_data(&self, raw_data: &Vec<String>) -> Result<EnergyMap, String> {
+        let map = raw_data
             .iter()
-            .map(|line| input_file::as_contig_unsigned_bytes(line))
-            .collect::<EnergyMap>()
+            .map(input_file::as_contig_unsigned_bytes)
+            .collect::<Result<EnergyMap, _>>()?;
+
+        let cols = map.first().map_or(0, |row| row.len());
+        if cols == 0 || map.iter().any(|row| row.len() != cols) {
+            return Err(String::from(
+                "Energy map rows must all have the same, non-zero width",
+            ));
+        }
+
+        Ok(map)
     }
 
-    fn solve_part_one(&self, map: &EnergyMap) {
+    fn solve_part_one(&self, map: &EnergyMap) -> Result<String, String> {
         let flashes = count_flashes(map, 100);
-        println!("{}", flashes);
+        Ok(flashes.to_string())
     }
 
-    fn solve_part_two(&self, map: &EnergyMap) {
+    fn solve_part_two(&self, map: &EnergyMap) -> Result<String, String> {
         let step = find_step_with_all_flashing(map);
-        println!("{}", step);
+        Ok(step.to_string())
     }
 }
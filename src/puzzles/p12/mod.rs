@@ -1,5 +1,5 @@
 use std::collections::HashMap;
-use std::fmt::{Debug, Formatter, Result};
+use std::fmt::{Debug, Formatter, Result as FmtResult};
 use std::hash::{Hash, Hasher};
 
 use crate::puzzles::puzzle::Puzzle;
@@ -82,7 +82,7 @@ impl PartialEq for Cave {
 }
 
 impl Debug for Cave {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         f.write_str(&self.id)
     }
 }
@@ -190,35 +190,37 @@ impl Puzzle<AdjacencyGraph> for P12 {
         12
     }
 
-    fn parse_data(&self, raw_data: &Vec<String>) -> AdjacencyGraph {
-        raw_data
+    fn parse_data(&self, raw_data: &Vec<String>) -> Result<AdjacencyGraph, String> {
+        let graph = raw_data
             .iter()
-            .fold(AdjacencyGraph::new(), |mut graph, line| {
-                let split_line: Vec<&str> = line.split('-').collect();
-                let nodes = split_line.into_iter().map(Cave::new).collect::<Vec<Cave>>();
+            .try_fold(AdjacencyGraph::new(), |mut graph, line| {
+                let (from, to) = line
+                    .trim()
+                    .split_once('-')
+                    .filter(|(from, to)| !from.is_empty() && !to.is_empty())
+                    .ok_or(format!("Couldn't parse connection: `{}`", line))?;
+                let (from, to) = (Cave::new(from), Cave::new(to));
 
                 // Can't impl Copy because Cave contains a String
-                let from = nodes[0].clone();
-                let to = nodes[1].clone();
-                let adjacents = graph.entry(to).or_insert(Vec::new());
-                adjacents.push(from);
+                let adjacents = graph.entry(to.clone()).or_insert(Vec::new());
+                adjacents.push(from.clone());
 
-                let from = nodes[0].clone();
-                let to = nodes[1].clone();
                 let adjacents = graph.entry(from).or_insert(Vec::new());
                 adjacents.push(to);
 
-                graph
-            })
+                Ok::<_, String>(graph)
+            })?;
+
+        Ok(graph)
     }
 
-    fn solve_part_one(&self, map: &AdjacencyGraph) {
+    fn solve_part_one(&self, map: &AdjacencyGraph) -> Result<String, String> {
         let path_count = count_paths(map, Mode::One);
-        println!("{}", path_count);
+        Ok(path_count.to_string())
     }
 
-    fn solve_part_two(&self, map: &AdjacencyGraph) {
+    fn solve_part_two(&self, map: &AdjacencyGraph) -> Result<String, String> {
         let path_count = count_paths(map, Mode::Two);
-        println!("{}", path_count);
+        Ok(path_count.to_string())
     }
 }
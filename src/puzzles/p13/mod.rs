@@ -245,20 +245,23 @@ fn repeatedly_fold_grid(instructions: &Vec<Instruction>, folds: Option<usize>) -
     grid
 }
 
-fn print_grid(grid: &Grid) {
+fn render_grid(grid: &Grid) -> String {
     let rows = grid.len();
     let cols = grid[0].len();
 
+    let mut rendered = String::new();
     for row in 0..rows {
         for col in 0..cols {
             if grid[row][col] {
-                print!("#");
+                rendered.push('#');
             } else {
-                print!(".");
+                rendered.push('.');
             }
         }
-        println!("");
+        rendered.push('\n');
     }
+
+    rendered.trim_end().to_string()
 }
 
 pub struct P13;
@@ -267,27 +270,22 @@ impl Puzzle<Vec<Instruction>> for P13 {
         13
     }
 
-    fn parse_data(&self, raw_data: &Vec<String>) -> Vec<Instruction> {
+    fn parse_data(&self, raw_data: &Vec<String>) -> Result<Vec<Instruction>, String> {
         raw_data
             .iter()
-            .filter_map(|line| {
-                if line.is_empty() {
-                    None
-                } else {
-                    Instruction::from_str(line.as_str()).ok()
-                }
-            })
+            .filter(|line| !line.is_empty())
+            .map(|line| Instruction::from_str(line.as_str()).map_err(String::from))
             .collect()
     }
 
-    fn solve_part_one(&self, instructions: &Vec<Instruction>) {
+    fn solve_part_one(&self, instructions: &Vec<Instruction>) -> Result<String, String> {
         let final_grid = repeatedly_fold_grid(instructions, Some(1));
         let dot_count = count_dots(&final_grid);
-        println!("{}", dot_count);
+        Ok(dot_count.to_string())
     }
 
-    fn solve_part_two(&self, instructions: &Vec<Instruction>) {
+    fn solve_part_two(&self, instructions: &Vec<Instruction>) -> Result<String, String> {
         let final_grid = repeatedly_fold_grid(instructions, None);
-        print_grid(&final_grid);
+        Ok(render_grid(&final_grid))
     }
 }
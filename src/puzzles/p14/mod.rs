@@ -33,7 +33,7 @@ impl FromStr for PairInsertionRule {
         if let [pair, element] = split_str.take(2).collect::<Vec<_>>()[..] {
             Ok(PairInsertionRule {
                 pair: String::from(pair),
-                element: element.chars().next().unwrap(),
+                element: element.chars().next().ok_or("Rule is missing an element")?,
             })
         } else {
             Err("Unable to parse rule")
@@ -172,12 +172,17 @@ impl Puzzle<Manual> for P14 {
         14
     }
 
-    fn parse_data(&self, raw_data: &Vec<String>) -> Manual {
-        let template = raw_data[0].to_string();
-        let insertion_rule_strs = &raw_data[2..];
+    fn parse_data(&self, raw_data: &Vec<String>) -> Result<Manual, String> {
+        let template = raw_data.first().ok_or("Missing template")?.to_string();
+        // Both answers count elements across pairs, so a lone element has nothing to count
+        if template.chars().count() < 2 {
+            return Err(String::from("Template must have at least two elements"));
+        }
+
         let insertion_rules =
-            insertion_rule_strs
+            raw_data
                 .iter()
+                .skip(2)
                 .fold(PairInsertionRules::new(), |mut rules, rule| {
                     if let Ok(PairInsertionRule { pair, element }) =
                         PairInsertionRule::from_str(&rule.to_owned())
@@ -189,19 +194,19 @@ impl Puzzle<Manual> for P14 {
                     }
                 });
 
-        Manual {
+        Ok(Manual {
             template,
             insertion_rules,
-        }
+        })
     }
 
-    fn solve_part_one(&self, manual: &Manual) {
+    fn solve_part_one(&self, manual: &Manual) -> Result<String, String> {
         let diff = insert_polymers_and_get_diff_btw_extremes(manual, 10);
-        println!("{}", diff);
+        Ok(diff.to_string())
     }
 
-    fn solve_part_two(&self, manual: &Manual) {
+    fn solve_part_two(&self, manual: &Manual) -> Result<String, String> {
         let diff = insert_polymers_and_get_diff_btw_extremes(manual, 40);
-        println!("{}", diff);
+        Ok(diff.to_string())
     }
 }
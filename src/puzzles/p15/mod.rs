@@ -198,24 +198,33 @@ impl Puzzle<RiskGridData> for P15 {
         15
     }
 
-    fn parse_data(&self, raw_data: &Vec<String>) -> RiskGridData {
-        raw_data
+    fn parse_data(&self, raw_data: &Vec<String>) -> Result<RiskGridData, String> {
+        let grid_data = raw_data
             .iter()
-            .map(|line| input_file::as_contig_unsigned_bytes(line))
-            .collect::<RiskGridData>()
+            .map(input_file::as_contig_unsigned_bytes)
+            .collect::<Result<RiskGridData, _>>()?;
+
+        let cols = grid_data.first().map_or(0, |row| row.len());
+        if cols == 0 || grid_data.iter().any(|row| row.len() != cols) {
+            return Err(String::from(
+                "Risk grid rows must all have the same, non-zero width",
+            ));
+        }
+
+        Ok(grid_data)
     }
 
-    fn solve_part_one(&self, grid_data: &RiskGridData) {
+    fn solve_part_one(&self, grid_data: &RiskGridData) -> Result<String, String> {
         let grid = RiskGrid::new(grid_data.to_vec(), 1, 1);
         let graph = Graph::new(&grid);
         let least_risky_path_size = compute_size_of_least_risky_path(&graph);
-        println!("{}", least_risky_path_size);
+        Ok(least_risky_path_size.to_string())
     }
 
-    fn solve_part_two(&self, grid_data: &RiskGridData) {
+    fn solve_part_two(&self, grid_data: &RiskGridData) -> Result<String, String> {
         let grid = RiskGrid::new(grid_data.to_vec(), 5, 5);
         let graph = Graph::new(&grid);
         let least_risky_path_size = compute_size_of_least_risky_path(&graph);
-        println!("{}", least_risky_path_size);
+        Ok(least_risky_path_size.to_string())
     }
 }
@@ -33,119 +33,132 @@ pub enum Packet {
     },
 }
 
-fn char_to_binary_string(ch: char) -> String {
+type PacketParseErr = &'static str;
+
+fn char_to_binary_string(ch: char) -> Result<String, PacketParseErr> {
     let value = match ch {
-        'A'..='F' => (ch as u8) - ('A' as u8) + 0xA,
-        _ => (ch as u8) - ('0' as u8),
+        'A'..='F' => (ch as u8) - b'A' + 0xA,
+        '0'..='9' => (ch as u8) - b'0',
+        _ => return Err("Message has a character that isn't hexadecimal"),
     };
 
     // Using # adds 0b prefix, which we don't want, and we also need a 4-chr string
-    format!("{:#06b}", value)[2..].to_string()
+    Ok(format!("{:#06b}", value)[2..].to_string())
 }
 
-fn parse_message(message: &String) -> Packet {
+fn parse_message(message: &str) -> Result<Packet, PacketParseErr> {
     let message_bin_str = message
+        .trim()
         .chars()
         .map(char_to_binary_string)
-        .collect::<Vec<_>>()
+        .collect::<Result<Vec<_>, _>>()?
         .join("");
 
-    parse_binary_string(&message_bin_str).0
+    parse_binary_string(&message_bin_str).map(|(packet, _)| packet)
 }
 
-fn parse_binary_string(bin_str: &str) -> (Packet, usize) {
+fn parse_binary_string(bin_str: &str) -> Result<(Packet, usize), PacketParseErr> {
     let mut cursor = 0usize;
 
-    let (version, version_bits) = read_bits(&bin_str[cursor..], 3);
+    let (version, version_bits) = read_bits(&bin_str[cursor..], 3)?;
     cursor += version_bits;
 
-    let (id, id_bits) = read_bits(&bin_str[cursor..], 3);
+    let (id, id_bits) = read_bits(&bin_str[cursor..], 3)?;
     cursor += id_bits;
 
     let literal = id == 4;
     if literal {
-        let (value, literal_bits) = read_literal_value(&bin_str[cursor..]);
+        let (value, literal_bits) = read_literal_value(&bin_str[cursor..])?;
         cursor += literal_bits;
 
-        (Packet::Literal { version, value }, cursor)
+        Ok((Packet::Literal { version, value }, cursor))
     } else {
-        let (is_num_subpackets, length_bits) = read_bool(&bin_str[cursor..]);
+        let (is_num_subpackets, length_bits) = read_bool(&bin_str[cursor..])?;
         cursor += length_bits;
 
         let mut subpackets = Vec::new();
         if is_num_subpackets {
-            let (num_subpackets, length_bits) = read_bits(&bin_str[cursor..], 11);
+            let (num_subpackets, length_bits) = read_bits(&bin_str[cursor..], 11)?;
             cursor += length_bits;
 
             for _ in 0..num_subpackets {
-                let (subpacket, subpacket_bits) = parse_binary_string(&bin_str[cursor..]);
+                let (subpacket, subpacket_bits) = parse_binary_string(&bin_str[cursor..])?;
                 cursor += subpacket_bits;
 
                 subpackets.push(subpacket);
             }
         } else {
             // Otherwise it's total number of bits the subpackets occupy
-            let (total_subpacket_bits, length_bits) = read_bits(&bin_str[cursor..], 15);
+            let (total_subpacket_bits, length_bits) = read_bits(&bin_str[cursor..], 15)?;
             cursor += length_bits;
 
+            let total_subpacket_bits = total_subpacket_bits as usize;
+            if cursor + total_subpacket_bits > bin_str.len() {
+                return Err("Subpackets run past the end of the message");
+            }
+
+            // Subpackets may only read within their declared length
+            let subpackets_bin_str = &bin_str[cursor..cursor + total_subpacket_bits];
             let mut subpacket_bits_read = 0usize;
-            while subpacket_bits_read < total_subpacket_bits.try_into().unwrap() {
-                let (subpacket, subpacket_bits) = parse_binary_string(&bin_str[cursor..]);
-                cursor += subpacket_bits;
+            while subpacket_bits_read < total_subpacket_bits {
+                let (subpacket, subpacket_bits) =
+                    parse_binary_string(&subpackets_bin_str[subpacket_bits_read..])?;
                 subpacket_bits_read += subpacket_bits;
 
                 subpackets.push(subpacket);
             }
+            cursor += subpacket_bits_read;
+        }
+
+        let operand: Operand =
+            num_traits::FromPrimitive::from_u64(id).ok_or("Unknown operator type")?;
+        let is_comparison = matches!(operand, Operand::Gt | Operand::Lt | Operand::Eql);
+        if is_comparison && subpackets.len() != 2 {
+            return Err("Comparison operators need exactly two subpackets");
+        }
+        if subpackets.is_empty() {
+            return Err("Operators need at least one subpacket");
         }
 
-        let operand = num_traits::FromPrimitive::from_u64(id).unwrap();
-        (
+        Ok((
             Packet::Operator {
                 operand,
                 version,
                 subpackets,
             },
             cursor,
-        )
+        ))
     }
 }
 
-fn read_bits(bin_str: &str, size: usize) -> (u64, usize) {
-    let mut length = size - 1;
-    let mut value = 0u64;
-
-    for bit in bin_str.chars() {
-        let bit_value = if bit == '1' { 1 } else { 0 };
-        value |= bit_value << length;
-
-        if length == 0 {
-            break;
-        }
-        length -= 1;
+fn read_bits(bin_str: &str, size: usize) -> Result<(u64, usize), PacketParseErr> {
+    if size > 64 {
+        return Err("Value doesn't fit in 64 bits");
+    }
+    if bin_str.len() < size {
+        return Err("Unexpected end of message");
     }
 
-    let bin_str_size = bin_str.len();
-    let bits_read = if bin_str_size <= size {
-        bin_str_size
-    } else {
-        size
-    };
-    (value, bits_read)
+    let value = bin_str[..size].chars().fold(0u64, |value, bit| {
+        (value << 1) | if bit == '1' { 1 } else { 0 }
+    });
+    Ok((value, size))
 }
 
-fn read_bool(bin_str: &str) -> (bool, usize) {
-    let value = bin_str.starts_with('1');
-    (value, 1)
+fn read_bool(bin_str: &str) -> Result<(bool, usize), PacketParseErr> {
+    read_bits(bin_str, 1).map(|(value, bits_read)| (value == 1, bits_read))
 }
 
-fn read_literal_value(bin_str: &str) -> (u64, usize) {
+fn read_literal_value(bin_str: &str) -> Result<(u64, usize), PacketParseErr> {
     let mut keep_reading = true;
     let mut bits_counted = 0usize;
     let mut literal_bin_str = String::new();
     while keep_reading {
-        let cur_group = &bin_str[bits_counted..bits_counted + 5];
+        let cur_group = bin_str
+            .get(bits_counted..bits_counted + 5)
+            .ok_or("Unexpected end of literal value")?;
         let continue_bit = &cur_group[0..1];
-        let (will_read_next, _) = read_bool(continue_bit);
+        let (will_read_next, _) = read_bool(continue_bit)?;
         bits_counted += 1;
         keep_reading = will_read_next;
 
@@ -154,8 +167,8 @@ fn read_literal_value(bin_str: &str) -> (u64, usize) {
         bits_counted += 4;
     }
 
-    let (value, _) = read_bits(&literal_bin_str[..], literal_bin_str.len());
-    (value, bits_counted)
+    let (value, _) = read_bits(&literal_bin_str[..], literal_bin_str.len())?;
+    Ok((value, bits_counted))
 }
 
 fn sum_versions(packet: &Packet) -> u64 {
@@ -246,18 +259,18 @@ impl Puzzle<Packet> for P16 {
         16
     }
 
-    fn parse_data(&self, raw_data: &Vec<String>) -> Packet {
-        let message = &raw_data[0];
-        parse_message(message)
+    fn parse_data(&self, raw_data: &Vec<String>) -> Result<Packet, String> {
+        let message = raw_data.first().ok_or("Missing message")?;
+        parse_message(message).map_err(String::from)
     }
 
-    fn solve_part_one(&self, packet: &Packet) {
+    fn solve_part_one(&self, packet: &Packet) -> Result<String, String> {
         let version_sum = sum_versions(packet);
-        println!("{}", version_sum);
+        Ok(version_sum.to_string())
     }
 
-    fn solve_part_two(&self, packet: &Packet) {
+    fn solve_part_two(&self, packet: &Packet) -> Result<String, String> {
         let result = evaluate(packet);
-        println!("{}", result);
+        Ok(result.to_string())
     }
 }
@@ -54,29 +54,32 @@ impl FromStr for Bounds {
         BOUNDS_REGEX
             .captures(line)
             .as_ref()
-            .map(extract_bounds)
             .ok_or("Failed to parse bounds")
+            .and_then(extract_bounds)
     }
 }
 
-fn parse_match_as_int(m: regex::Match) -> i32 {
-    m.as_str().parse().expect("Failed to parse integer")
+fn parse_match_as_int(m: regex::Match) -> Result<i32, BoundsParseErr> {
+    m.as_str().parse().or(Err("Failed to parse integer"))
 }
 
-fn extract_bounds(captures: &regex::Captures) -> Bounds {
-    let x_min = captures.name("x_min").map(parse_match_as_int);
-    let x_max = captures.name("x_max").map(parse_match_as_int);
-    let xs = x_min
-        .zip(x_max)
-        .expect("Failed to parse both x_min and x_max");
+fn extract_bound(captures: &regex::Captures, name: &str) -> Result<i32, BoundsParseErr> {
+    captures
+        .name(name)
+        .ok_or("Failed to parse bounds")
+        .and_then(parse_match_as_int)
+}
 
-    let y_min = captures.name("y_min").map(parse_match_as_int);
-    let y_max = captures.name("y_max").map(parse_match_as_int);
-    let ys = y_min
-        .zip(y_max)
-        .expect("Failed to parse both y_min and y_max");
+fn extract_bounds(captures: &regex::Captures) -> Result<Bounds, BoundsParseErr> {
+    let x_min = extract_bound(captures, "x_min")?;
+    let x_max = extract_bound(captures, "x_max")?;
+    let y_min = extract_bound(captures, "y_min")?;
+    let y_max = extract_bound(captures, "y_max")?;
 
-    Bounds { x: xs, y: ys }
+    Ok(Bounds {
+        x: (x_min, x_max),
+        y: (y_min, y_max),
+    })
 }
 
 fn find_max_y_over_trajectories(bounds: &Bounds) -> Report {
@@ -152,19 +155,20 @@ impl Puzzle<Bounds> for P17 {
         17
     }
 
-    fn parse_data(&self, raw_data: &Vec<String>) -> Bounds {
-        Bounds::from_str(&raw_data[0]).unwrap()
+    fn parse_data(&self, raw_data: &Vec<String>) -> Result<Bounds, String> {
+        let line = raw_data.first().ok_or("Missing target area")?;
+        Bounds::from_str(line).map_err(String::from)
     }
 
-    fn solve_part_one(&self, bounds: &Bounds) {
+    fn solve_part_one(&self, bounds: &Bounds) -> Result<String, String> {
         let Report { max_y, .. } = find_max_y_over_trajectories(bounds);
-        println!("{}", max_y);
+        Ok(max_y.to_string())
     }
 
-    fn solve_part_two(&self, bounds: &Bounds) {
+    fn solve_part_two(&self, bounds: &Bounds) -> Result<String, String> {
         let Report {
             unique_vels_count, ..
         } = find_max_y_over_trajectories(bounds);
-        println!("{}", unique_vels_count);
+        Ok(unique_vels_count.to_string())
     }
 }
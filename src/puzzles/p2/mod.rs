@@ -1,105 +1,303 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::rc::Rc;
 use std::str::FromStr;
 
+use lazy_static::lazy_static;
 use regex::Regex;
 
 use crate::puzzles::puzzle::Puzzle;
+use crate::puzzles::report::ReportOptions;
 
-pub enum Direction {
-    Forward,
-    Down,
-    Up,
+lazy_static! {
+    static ref COMMAND_REGEX: Regex = Regex::new(r"^(?P<verb>[a-z]+)\s+(?P<value>-?\d+)$").unwrap();
 }
 
 pub struct Command {
-    direction: Direction,
-    value: u8,
+    verb: String,
+    value: i64,
 }
 
 impl Command {
-    pub fn new((direction, value): (Direction, u8)) -> Self {
-        Self { direction, value }
+    pub fn new(verb: &str, value: i64) -> Self {
+        Command {
+            verb: verb.to_string(),
+            value,
+        }
+    }
+
+    pub fn verb(&self) -> &str {
+        &self.verb
     }
-}
 
-struct Position {
-    x: i32,
-    y: i32,
+    pub fn value(&self) -> i64 {
+        self.value
+    }
 }
 
 impl FromStr for Command {
     type Err = String;
 
     fn from_str(command_str: &str) -> Result<Self, Self::Err> {
-        let command_regex = Regex::new(r"(?P<direction>forward|up|down)\s(?P<value>\d+)").unwrap();
-        let captures = command_regex.captures(command_str).unwrap();
-        let direction_str = captures.name("direction").unwrap().as_str();
-        let direction = match direction_str {
-            "forward" => Some(Direction::Forward),
-            "down" => Some(Direction::Down),
-            "up" => Some(Direction::Up),
-            _ => None,
-        };
+        let captures = COMMAND_REGEX
+            .captures(command_str.trim())
+            .ok_or(format!("Couldn't parse command: `{}`", command_str))?;
 
-        let value_str = captures.name("value").unwrap().as_str();
-        let value = match value_str.parse::<u8>() {
-            Ok(value) => Some(value),
-            Err(_) => None,
-        };
+        let verb = captures.name("verb").unwrap().as_str().to_string();
+        let value = captures
+            .name("value")
+            .unwrap()
+            .as_str()
+            .parse::<i64>()
+            .or(Err(format!("Couldn't parse command: `{}`", command_str)))?;
 
-        direction
-            .zip(value)
-            .map(Command::new)
-            .ok_or(String::from(format!(
-                "Couldn't parse command: `{}`",
-                command_str
-            )))
-    }
-}
-
-fn compute_position(commands: &Vec<Command>) -> Position {
-    let initial_position = Position { x: 0, y: 0 };
-    commands
-        .iter()
-        .fold(initial_position, |acc, command| match command.direction {
-            Direction::Forward => Position {
-                x: acc.x + command.value as i32,
-                ..acc
-            },
-            Direction::Down => Position {
-                y: acc.y + command.value as i32,
-                ..acc
-            },
-            Direction::Up => Position {
-                y: acc.y - command.value as i32,
-                ..acc
-            },
-        })
+        Ok(Command { verb, value })
+    }
+}
+
+// Registers hold any extra state a custom verb wants to keep (e.g. a heading for `turn`)
+#[derive(Default, Clone)]
+pub struct Submarine {
+    position: i64,
+    depth: i64,
+    aim: i64,
+    registers: BTreeMap<String, i64>,
+}
+
+type SubmarineErr = String;
+
+fn checked(value: Option<i64>, what: &str) -> Result<i64, SubmarineErr> {
+    value.ok_or(format!("{} overflows an i64", what))
+}
+
+// Handlers only get at the state through these, so every built-in or custom verb moves the
+// submarine with overflow checks
+impl Submarine {
+    pub fn position(&self) -> i64 {
+        self.position
+    }
+
+    pub fn depth(&self) -> i64 {
+        self.depth
+    }
+
+    pub fn aim(&self) -> i64 {
+        self.aim
+    }
+
+    // Registers that were never set read as 0
+    pub fn register(&self, name: &str) -> i64 {
+        self.registers.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn registers(&self) -> &BTreeMap<String, i64> {
+        &self.registers
+    }
+
+    pub fn change_position(&mut self, delta: i64) -> Result<(), SubmarineErr> {
+        self.position = checked(self.position.checked_add(delta), "Position")?;
+        Ok(())
+    }
+
+    pub fn change_depth(&mut self, delta: i64) -> Result<(), SubmarineErr> {
+        self.depth = checked(self.depth.checked_add(delta), "Depth")?;
+        Ok(())
+    }
+
+    pub fn change_aim(&mut self, delta: i64) -> Result<(), SubmarineErr> {
+        self.aim = checked(self.aim.checked_add(delta), "Aim")?;
+        Ok(())
+    }
+
+    pub fn set_register(&mut self, name: &str, value: i64) {
+        self.registers.insert(name.to_string(), value);
+    }
+}
+
+type Handler = Rc<dyn Fn(&mut Submarine, i64) -> Result<(), SubmarineErr>>;
+
+// Submarine state after each command, in order
+pub struct Trace {
+    steps: Vec<Submarine>,
 }
 
-fn compute_position_with_aim(commands: &Vec<Command>) -> Position {
-    let initial_position = Position { x: 0, y: 0 };
-    let initial_aim = 0i32;
-    let initial_conditions = (initial_position, initial_aim);
+impl Trace {
+    pub fn steps(&self) -> &[Submarine] {
+        &self.steps
+    }
 
-    commands
-        .iter()
-        .fold(initial_conditions, |conditions, command| {
-            let (pos, aim) = conditions;
-            match command.direction {
-                Direction::Forward => (
-                    Position {
-                        x: pos.x + command.value as i32,
-                        y: pos.y + (aim * command.value as i32),
-                    },
-                    aim,
-                ),
+    pub fn max_depth(&self) -> Option<(usize, i64)> {
+        self.steps
+            .iter()
+            .enumerate()
+            .max_by_key(|(index, sub)| (sub.depth, Reverse(*index)))
+            .map(|(index, sub)| (index, sub.depth))
+    }
 
-                Direction::Down => (Position { ..pos }, aim + command.value as i32),
+    pub fn first_deeper_than(&self, threshold: i64) -> Option<usize> {
+        self.steps.iter().position(|sub| sub.depth > threshold)
+    }
 
-                Direction::Up => (Position { ..pos }, aim - command.value as i32),
+    pub fn to_csv(&self, commands: &[Command]) -> String {
+        let register_names = self
+            .steps
+            .iter()
+            .flat_map(|sub| sub.registers.keys())
+            .collect::<BTreeSet<_>>();
+
+        let mut csv = String::from("step,command,position,depth,aim");
+        for name in &register_names {
+            csv += &format!(",{}", name);
+        }
+
+        for (index, (sub, command)) in self.steps.iter().zip(commands).enumerate() {
+            csv += &format!(
+                "\n{},{} {},{},{},{}",
+                index, command.verb, command.value, sub.position, sub.depth, sub.aim
+            );
+            for name in &register_names {
+                let value = sub
+                    .registers
+                    .get(*name)
+                    .map_or(String::new(), |v| v.to_string());
+                csv += &format!(",{}", value);
             }
+        }
+
+        csv
+    }
+}
+
+#[derive(Default)]
+pub struct Interpreter {
+    handlers: HashMap<String, Handler>,
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(
+        mut self,
+        verb: &str,
+        handler: impl Fn(&mut Submarine, i64) -> Result<(), SubmarineErr> + 'static,
+    ) -> Self {
+        self.handlers.insert(verb.to_string(), Rc::new(handler));
+        self
+    }
+
+    // Part one: up/down move the submarine directly
+    pub fn simple() -> Self {
+        Interpreter::new()
+            .with("forward", |sub, value| sub.change_position(value))
+            .with("down", |sub, value| sub.change_depth(value))
+            .with("up", |sub, value| sub.change_depth(negate(value)?))
+    }
+
+    // Part two: up/down only tilt the submarine, forward moves along the aim
+    pub fn with_aim() -> Self {
+        Interpreter::new()
+            .with("forward", |sub, value| {
+                sub.change_position(value)?;
+                sub.change_depth(checked(sub.aim.checked_mul(value), "Depth")?)
+            })
+            .with("down", |sub, value| sub.change_aim(value))
+            .with("up", |sub, value| sub.change_aim(negate(value)?))
+    }
+
+    // `back` reverses whatever `forward` does in this interpreter, and `turn` keeps a compass
+    // heading in degrees in the `heading` register
+    pub fn with_manoeuvres(self) -> Self {
+        let forward = self.handlers.get("forward").cloned();
+        self.with("back", move |sub, value| match &forward {
+            Some(forward) => forward(sub, negate(value)?),
+            None => Err(String::from("`back` needs a `forward` command to reverse")),
+        })
+        .with("turn", |sub, value| {
+            let heading = (sub.register("heading") + value % 360).rem_euclid(360);
+            sub.set_register("heading", heading);
+            Ok(())
         })
-        .0
+    }
+
+    pub fn step(&self, submarine: &mut Submarine, command: &Command) -> Result<(), String> {
+        let handler = self
+            .handlers
+            .get(&command.verb)
+            .ok_or(format!("Unknown command: `{}`", command.verb))?;
+        handler(submarine, command.value)
+    }
+
+    pub fn run(&self, commands: &[Command]) -> Result<Submarine, String> {
+        let mut submarine = Submarine::default();
+        for command in commands {
+            self.step(&mut submarine, command)?;
+        }
+
+        Ok(submarine)
+    }
+
+    pub fn trace(&self, commands: &[Command]) -> Result<Trace, String> {
+        let mut submarine = Submarine::default();
+        let mut steps = Vec::with_capacity(commands.len());
+        for command in commands {
+            self.step(&mut submarine, command)?;
+            steps.push(submarine.clone());
+        }
+
+        Ok(Trace { steps })
+    }
+}
+
+fn negate(value: i64) -> Result<i64, SubmarineErr> {
+    checked(value.checked_neg(), "Command value")
+}
+
+fn interpreter_from_options(options: &ReportOptions) -> Result<Interpreter, String> {
+    let interpreter = match options.get("mode", String::from("aim"))?.as_str() {
+        "simple" => Interpreter::simple(),
+        "aim" => Interpreter::with_aim(),
+        mode => return Err(format!("Unknown mode: `{}`", mode)),
+    };
+
+    if options.get("manoeuvres", false)? {
+        Ok(interpreter.with_manoeuvres())
+    } else {
+        Ok(interpreter)
+    }
+}
+
+fn format_course_report(trace: &Trace, threshold: Option<i64>) -> String {
+    let submarine = trace.steps.last().cloned().unwrap_or_default();
+    let mut report = format!(
+        "position: {}\ndepth: {}\naim: {}",
+        submarine.position, submarine.depth, submarine.aim
+    );
+    for (name, value) in &submarine.registers {
+        report += &format!("\n{}: {}", name, value);
+    }
+
+    if let Some((step, depth)) = trace.max_depth() {
+        report += &format!("\nmax depth: {} (after step {})", depth, step);
+    }
+
+    if let Some(threshold) = threshold {
+        report += &match trace.first_deeper_than(threshold) {
+            Some(step) => format!("\nfirst deeper than {}: step {}", threshold, step),
+            None => format!("\nnever deeper than {}", threshold),
+        };
+    }
+
+    report
+}
+
+fn multiply_final_position(interpreter: &Interpreter, commands: &[Command]) -> Result<i64, String> {
+    let submarine = interpreter.run(commands)?;
+    checked(
+        submarine.position.checked_mul(submarine.depth),
+        "Final position",
+    )
 }
 
 pub struct P2;
@@ -108,20 +306,40 @@ impl Puzzle<Vec<Command>> for P2 {
         2
     }
 
-    fn parse_data(&self, raw_data: &Vec<String>) -> Vec<Command> {
+    fn parse_data(&self, raw_data: &Vec<String>) -> Result<Vec<Command>, String> {
         raw_data
             .iter()
-            .map(|line| Command::from_str(line.as_str()).unwrap())
+            .map(|line| Command::from_str(line.as_str()))
             .collect()
     }
 
-    fn solve_part_one(&self, commands: &Vec<Command>) {
-        let position = compute_position(commands);
-        println!("{}", position.x * position.y)
+    fn solve_part_one(&self, commands: &Vec<Command>) -> Result<String, String> {
+        let product = multiply_final_position(&Interpreter::simple(), commands)?;
+        Ok(product.to_string())
+    }
+
+    fn solve_part_two(&self, commands: &Vec<Command>) -> Result<String, String> {
+        let product = multiply_final_position(&Interpreter::with_aim(), commands)?;
+        Ok(product.to_string())
     }
 
-    fn solve_part_two(&self, commands: &Vec<Command>) {
-        let position = compute_position_with_aim(commands);
-        println!("{}", position.x * position.y)
+    fn report(
+        &self,
+        commands: &Vec<Command>,
+        name: &str,
+        options: &ReportOptions,
+    ) -> Result<String, String> {
+        match name {
+            "course" => {
+                let threshold = options.get_optional::<i64>("threshold")?;
+                let trace = interpreter_from_options(options)?.trace(commands)?;
+                Ok(format_course_report(&trace, threshold))
+            }
+            "trace" => {
+                let trace = interpreter_from_options(options)?.trace(commands)?;
+                Ok(trace.to_csv(commands))
+            }
+            _ => Err(format!("Puzzle 2 has no `{}` report", name)),
+        }
     }
 }
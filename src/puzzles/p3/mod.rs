@@ -1,109 +1,351 @@
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
 use crate::puzzles::puzzle::Puzzle;
-use crate::utils::input_file;
+use crate::puzzles::report::ReportOptions;
+
+type ReportParseErr = &'static str;
 
-pub struct Rates {
-    gamma: u32,
-    epsilon: u32,
+// Lines are packed into u64s and kept sorted, so every line sharing a prefix sits in one
+// contiguous run. Columns are also stored as bitsets across lines for popcount-based counts.
+pub struct DiagnosticReport {
+    width: usize,
+    lines: Vec<u64>,
+    columns: Vec<Vec<u64>>,
 }
 
-impl Rates {
-    pub fn new() -> Self {
-        Self { gamma: 0u32, epsilon: 0u32 }
+impl DiagnosticReport {
+    pub fn new(width: usize, mut lines: Vec<u64>) -> Self {
+        lines.sort_unstable();
+
+        let words = lines.len().div_ceil(64);
+        let mut columns = vec![vec![0u64; words]; width];
+        for (index, line) in lines.iter().enumerate() {
+            for (column, bitset) in columns.iter_mut().enumerate() {
+                if line & column_mask(width, column) != 0 {
+                    bitset[index / 64] |= 1 << (index % 64);
+                }
+            }
+        }
+
+        Self {
+            width,
+            lines,
+            columns,
+        }
     }
 
-    pub fn multiply_values(&self) -> u32 {
-        self.gamma * self.epsilon
+    pub fn count_bits_at(&self, column: usize) -> (usize, usize) {
+        let ones = self.columns[column]
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum();
+        (ones, self.lines.len() - ones)
     }
 }
 
-fn power_consumption(report: &Vec<Vec<char>>) -> u32 {
-    let bit_width = report[0].len();
-    let report_size = report.len();
+// Column 0 is the leftmost character, i.e. the most significant bit
+fn column_mask(width: usize, column: usize) -> u64 {
+    1 << (width - column - 1)
+}
 
-    (0..bit_width).fold(Rates::new(), |rates, bit| {
-        let (ones, zeroes) = count_bits_at(bit, report_size, report);
-        let bit_to_set = bit_width - bit - 1;
-        let value_to_merge = 1 << bit_to_set;
-        if ones >= zeroes {
-            Rates { gamma: rates.gamma | value_to_merge, ..rates }
-        } else {
-            Rates { epsilon: rates.epsilon | value_to_merge, ..rates }
-        }
-    }).multiply_values()
+fn parse_line(line: &str) -> Result<u64, ReportParseErr> {
+    line.chars().try_fold(0u64, |number, chr| match chr {
+        '0' => Ok(number << 1),
+        '1' => Ok((number << 1) | 1),
+        _ => Err("Report lines may only contain 0 and 1"),
+    })
 }
 
-fn life_support_rating(report: &Vec<Vec<char>>) -> u32 {
-    let bit_width = report[0].len();
+fn parse_report(raw_data: &[String]) -> Result<DiagnosticReport, ReportParseErr> {
+    let width = raw_data.first().map_or(0, |line| line.trim().len());
+    if width == 0 || width > 64 {
+        return Err("Report lines must be 1 to 64 bits wide");
+    }
 
-    let oxygen_rating = get_rating(report, true, bit_width);
-    let co2_rating = get_rating(report, false, bit_width);
+    let lines = raw_data
+        .iter()
+        .map(|line| line.trim())
+        .map(|line| {
+            if line.len() == width {
+                parse_line(line)
+            } else {
+                Err("Report lines must all be the same width")
+            }
+        })
+        .collect::<Result<Vec<u64>, _>>()?;
 
-    oxygen_rating * co2_rating
+    Ok(DiagnosticReport::new(width, lines))
 }
 
-fn get_rating(report: &Vec<Vec<char>>, for_oxygen: bool, bit_width: usize) -> u32 {
-    let mut kept_nums = report.clone();
-    for bit in 0..bit_width {
-        let (ones, zeroes) = count_bits_at(bit, kept_nums.len(), &kept_nums);
-        kept_nums.retain(|n: &Vec<char>| {
-            let keep_one = if for_oxygen {
-                ones >= zeroes
-            } else {
-                ones < zeroes
-            };
-            keep_one && n[bit] == '1' || !keep_one && n[bit] == '0'
-        });
+#[derive(Clone, Copy)]
+pub enum Commonality {
+    Most,
+    Least,
+}
 
-        if kept_nums.len() == 1 {
-            break;
+// Which bit a rating keeps at each column, and which one wins when both are equally common
+#[derive(Clone, Copy)]
+pub struct Criteria {
+    commonality: Commonality,
+    tie_bit: bool,
+}
+
+impl Criteria {
+    pub const OXYGEN: Criteria = Criteria {
+        commonality: Commonality::Most,
+        tie_bit: true,
+    };
+    pub const CO2: Criteria = Criteria {
+        commonality: Commonality::Least,
+        tie_bit: false,
+    };
+
+    pub fn keep_bit(&self, ones: usize, zeroes: usize) -> bool {
+        if ones == zeroes {
+            return self.tie_bit;
+        }
+
+        match self.commonality {
+            Commonality::Most => ones > zeroes,
+            Commonality::Least => ones < zeroes,
         }
     }
+}
+
+// e.g. `most:1` or `least:0`
+impl FromStr for Criteria {
+    type Err = String;
 
-    build_number_from_chars(&kept_nums[0])
+    fn from_str(criteria_str: &str) -> Result<Self, Self::Err> {
+        let (commonality, tie_bit) = criteria_str.split_once(':').ok_or(format!(
+            "Criteria look like most:1 or least:0, got `{}`",
+            criteria_str
+        ))?;
+
+        let commonality = match commonality {
+            "most" => Commonality::Most,
+            "least" => Commonality::Least,
+            _ => return Err(format!("Unknown commonality: `{}`", commonality)),
+        };
+        let tie_bit = match tie_bit {
+            "1" => true,
+            "0" => false,
+            _ => return Err(format!("Tie bit must be 0 or 1, got `{}`", tie_bit)),
+        };
+
+        Ok(Criteria {
+            commonality,
+            tie_bit,
+        })
+    }
 }
 
-fn build_number_from_chars(chars: &Vec<char>) -> u32 {
-    let bit_width = chars.len();
-    (0..bit_width).fold(0u32, |number, bit| {
-        let bit_to_set = bit_width - bit - 1;
-        if chars[bit] == '1' {
-            let value_to_merge = 1 << bit_to_set;
-            number | value_to_merge
-        } else {
-            number
-        }
-    })
+impl fmt::Display for Criteria {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let commonality = match self.commonality {
+            Commonality::Most => "most",
+            Commonality::Least => "least",
+        };
+        write!(f, "{}:{}", commonality, u8::from(self.tie_bit))
+    }
+}
+
+// e.g. `oxygen:most:1`
+pub struct NamedCriteria {
+    name: String,
+    criteria: Criteria,
+}
+
+impl FromStr for NamedCriteria {
+    type Err = String;
+
+    fn from_str(named_str: &str) -> Result<Self, Self::Err> {
+        let (name, criteria) = named_str.split_once(':').ok_or(format!(
+            "Ratings look like name:most:1, got `{}`",
+            named_str
+        ))?;
+
+        Ok(NamedCriteria {
+            name: name.to_string(),
+            criteria: Criteria::from_str(criteria)?,
+        })
+    }
+}
+
+// Lines dropped at one column, as a range of the report's sorted lines
+pub struct Elimination {
+    column: usize,
+    ones: usize,
+    zeroes: usize,
+    kept_bit: bool,
+    eliminated: Range<usize>,
+}
+
+pub struct Rating {
+    value: u64,
+    eliminations: Vec<Elimination>,
+}
+
+// Applies the criteria to each column's counts over the whole report, as gamma and epsilon do
+fn column_value(report: &DiagnosticReport, criteria: Criteria) -> u64 {
+    (0..report.width)
+        .filter(|&column| {
+            let (ones, zeroes) = report.count_bits_at(column);
+            criteria.keep_bit(ones, zeroes)
+        })
+        .fold(0, |value, column| value | column_mask(report.width, column))
+}
+
+// Products of two values up to 64 bits wide, so they only fit once widened
+fn power_consumption(report: &DiagnosticReport) -> u128 {
+    let gamma = column_value(report, Criteria::OXYGEN);
+    let epsilon = column_value(report, Criteria::CO2);
+
+    u128::from(gamma) * u128::from(epsilon)
+}
+
+fn life_support_rating(report: &DiagnosticReport) -> u128 {
+    let oxygen_rating = get_rating(report, Criteria::OXYGEN).value;
+    let co2_rating = get_rating(report, Criteria::CO2).value;
+
+    u128::from(oxygen_rating) * u128::from(co2_rating)
 }
 
-fn count_bits_at(bit: usize, report_size: usize, report: &Vec<Vec<char>>) -> (usize, usize) {
-    let ones = report.iter().fold(0, |acc, number| {
-        let bit_value = number[bit];
-        if bit_value == '1' {
-            acc + 1
+// Narrows a range of the sorted lines one column at a time. Within the range every line
+// shares the columns already seen, so zeroes precede ones and a binary search splits them.
+fn get_rating(report: &DiagnosticReport, criteria: Criteria) -> Rating {
+    let lines = &report.lines;
+    let (mut start, mut end) = (0, lines.len());
+    let mut eliminations = Vec::new();
+
+    for column in 0..report.width {
+        if end - start <= 1 {
+            break;
+        }
+
+        let mask = column_mask(report.width, column);
+        let split = start + lines[start..end].partition_point(|line| line & mask == 0);
+        let (ones, zeroes) = (end - split, split - start);
+
+        // When every remaining line has the same bit here, the least common bit is one no line
+        // has, which happens with ordinary distinct lines too (CO2 on 01101 and 01010 at column
+        // 1). Rather than narrowing down to nothing, keep the bit they share. That departs from a
+        // strict keep-least-common rule, silently and only in this case.
+        let kept_bit = criteria.keep_bit(ones, zeroes) && ones > 0 || zeroes == 0;
+        let eliminated = if kept_bit { start..split } else { split..end };
+        if kept_bit {
+            start = split;
         } else {
-            acc
+            end = split;
         }
-    });
 
-    let zeroes = report_size - ones;
-    (ones, zeroes)
+        eliminations.push(Elimination {
+            column,
+            ones,
+            zeroes,
+            kept_bit,
+            eliminated,
+        });
+    }
+
+    Rating {
+        value: lines[start],
+        eliminations,
+    }
+}
+
+fn format_line(report: &DiagnosticReport, line: u64) -> String {
+    format!("{:0width$b}", line, width = report.width)
+}
+
+fn format_ratings_report(report: &DiagnosticReport, ratings: &[NamedCriteria]) -> String {
+    ratings
+        .iter()
+        .map(|named| {
+            let rating = get_rating(report, named.criteria);
+            format!(
+                "{} ({}): rating {} ({}), column value {}",
+                named.name,
+                named.criteria,
+                rating.value,
+                format_line(report, rating.value),
+                column_value(report, named.criteria)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn format_eliminations_report(report: &DiagnosticReport, criteria: Criteria) -> String {
+    let rating = get_rating(report, criteria);
+    let mut output = format!("criteria: {}\nrating: {}", criteria, rating.value);
+
+    output += "\ncolumn,ones,zeroes,kept,eliminated";
+    for step in &rating.eliminations {
+        let eliminated = report.lines[step.eliminated.clone()]
+            .iter()
+            .map(|&line| format_line(report, line))
+            .collect::<Vec<String>>()
+            .join(" ");
+        output += &format!(
+            "\n{},{},{},{},{}",
+            step.column,
+            step.ones,
+            step.zeroes,
+            u8::from(step.kept_bit),
+            eliminated
+        );
+    }
+
+    output
 }
 
 pub struct P3;
-impl Puzzle<Vec<Vec<char>>> for P3 {
+impl Puzzle<DiagnosticReport> for P3 {
     fn number(&self) -> u8 {
         3
     }
 
-    fn parse_data(&self, raw_data: &Vec<String>) -> Vec<Vec<char>> {
-        raw_data.iter().map(input_file::as_chars).collect()
+    fn parse_data(&self, raw_data: &Vec<String>) -> Result<DiagnosticReport, String> {
+        parse_report(raw_data).map_err(String::from)
+    }
+
+    fn solve_part_one(&self, report: &DiagnosticReport) -> Result<String, String> {
+        Ok(power_consumption(report).to_string())
     }
 
-    fn solve_part_one(&self, report: &Vec<Vec<char>>) {
-        println!("{}", power_consumption(report))
+    fn solve_part_two(&self, report: &DiagnosticReport) -> Result<String, String> {
+        Ok(life_support_rating(report).to_string())
     }
 
-    fn solve_part_two(&self, report: &Vec<Vec<char>>) {
-        println!("{}", life_support_rating(report))
+    fn report(
+        &self,
+        report: &DiagnosticReport,
+        name: &str,
+        options: &ReportOptions,
+    ) -> Result<String, String> {
+        match name {
+            "ratings" => {
+                let default_ratings = vec![
+                    NamedCriteria {
+                        name: String::from("oxygen"),
+                        criteria: Criteria::OXYGEN,
+                    },
+                    NamedCriteria {
+                        name: String::from("co2"),
+                        criteria: Criteria::CO2,
+                    },
+                ];
+                let ratings = options.get_list("ratings", default_ratings)?;
+                Ok(format_ratings_report(report, &ratings))
+            }
+            "eliminations" => {
+                let criteria = options.get("criteria", Criteria::OXYGEN)?;
+                Ok(format_eliminations_report(report, criteria))
+            }
+            _ => Err(format!("Puzzle 3 has no `{}` report", name)),
+        }
     }
 }
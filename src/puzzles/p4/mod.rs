@@ -1,156 +1,335 @@
-use std::collections::HashSet;
-
-use itertools::Itertools;
+use std::collections::HashMap;
+use std::fmt;
 
 use crate::puzzles::puzzle::Puzzle;
+use crate::puzzles::report::ReportOptions;
 
-type Indices = Vec<(usize, usize)>;
-type Board = [[u32; 5]; 5];
-type NumSet = HashSet<u32>;
-type IdxSet = HashSet<usize>;
+// Square board stored row by row
+pub struct Board {
+    size: usize,
+    cells: Vec<u32>,
+}
 
 pub struct Subsystem {
     draw_order: Vec<u32>,
     boards: Vec<Board>,
 }
 
-struct Winner {
-    index: usize,
-    marked_nums: NumSet,
-    last_number: u32,
+#[derive(Clone, Copy, Default)]
+pub struct Rules {
+    diagonals: bool,
 }
 
-fn read_boards(raw_data: &[String]) -> Vec<Board> {
-    let mut boards = Vec::new();
-    let mut cur_board = [[0u32; 5]; 5];
-    let mut row: usize = 0;
-    let lines = raw_data.len();
-    for (index, line) in raw_data.iter().enumerate() {
-        let nums: Vec<u32> = line
-            .trim()
-            .split_whitespace()
-            .map(|n| n.parse().unwrap())
-            .collect();
+#[derive(Clone, Copy)]
+pub enum Line {
+    Row(usize),
+    Column(usize),
+    Diagonal,
+    AntiDiagonal,
+}
 
-        for (col, num) in nums.iter().enumerate() {
-            cur_board[row][col] = *num;
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Line::Row(row) => write!(f, "row {}", row),
+            Line::Column(col) => write!(f, "column {}", col),
+            Line::Diagonal => write!(f, "diagonal"),
+            Line::AntiDiagonal => write!(f, "anti-diagonal"),
         }
-        row += 1;
+    }
+}
 
-        if nums.len() == 0 || index == lines - 1 {
-            boards.push(cur_board.clone());
+pub struct Win {
+    board: usize,
+    draw: usize,
+    number: u32,
+    line: Line,
+    // None when the score doesn't fit a u64
+    score: Option<u64>,
+}
 
-            // Clear array
-            for row in cur_board.iter_mut() {
-                for num in row.iter_mut() {
-                    *num = 0u32;
-                }
-            }
+type SubsystemParseErr = &'static str;
+
+fn parse_num(num: &str) -> Result<u32, SubsystemParseErr> {
+    num.trim().parse().or(Err("Unable to parse number"))
+}
+
+fn read_draw_order(line: &str) -> Result<Vec<u32>, SubsystemParseErr> {
+    line.split(',').map(parse_num).collect()
+}
+
+// Boards are separated by blank lines
+fn read_boards(raw_data: &[String]) -> Result<Vec<Board>, SubsystemParseErr> {
+    raw_data
+        .split(|line| line.trim().is_empty())
+        .filter(|rows| !rows.is_empty())
+        .map(read_board)
+        .collect()
+}
+
+fn read_board(rows: &[String]) -> Result<Board, SubsystemParseErr> {
+    let size = rows.len();
+    let mut cells = Vec::with_capacity(size * size);
+    for line in rows {
+        let nums = line
+            .split_whitespace()
+            .map(parse_num)
+            .collect::<Result<Vec<u32>, _>>()?;
 
-            row = 0;
+        if nums.len() != size {
+            return Err("Board must have as many numbers per row as it has rows");
         }
+        cells.extend(nums);
     }
 
-    boards
-}
-
-fn find_first_winner(subsystem: &Subsystem) -> Option<Winner> {
-    let mut marked_nums = NumSet::new();
-    marked_nums.extend(&subsystem.draw_order[0..4]);
-    for num in &subsystem.draw_order[4..] {
-        marked_nums.insert(*num);
-        for (index, board) in subsystem.boards.iter().enumerate() {
-            let winner = is_winner(board, &marked_nums);
-            if winner {
-                return Some(Winner {
-                    index,
-                    marked_nums,
-                    last_number: *num,
-                });
-            }
+    Ok(Board { size, cells })
+}
+
+impl Board {
+    fn lines(&self, rules: Rules) -> Vec<Line> {
+        let mut lines = (0..self.size).map(Line::Row).collect::<Vec<_>>();
+        lines.extend((0..self.size).map(Line::Column));
+        if rules.diagonals {
+            lines.extend([Line::Diagonal, Line::AntiDiagonal]);
         }
+        lines
     }
 
-    None
+    fn line_numbers(&self, line: Line) -> Vec<u32> {
+        let size = self.size;
+        (0..size)
+            .map(|step| match line {
+                Line::Row(row) => row * size + step,
+                Line::Column(col) => step * size + col,
+                Line::Diagonal => step * size + step,
+                Line::AntiDiagonal => step * size + (size - step - 1),
+            })
+            .map(|cell| self.cells[cell])
+            .collect()
+    }
 }
 
-fn find_last_winner(subsystem: &Subsystem) -> Option<Winner> {
-    let mut winners = Vec::new();
+// Every board's win in the order they happen, plus the boards still waiting when the draws run out
+pub struct Timeline {
+    wins: Vec<Win>,
+    never_won: Vec<usize>,
+}
 
-    let mut marked_nums = NumSet::new();
-    marked_nums.extend(&subsystem.draw_order[0..4]);
+// Hit counters per line, so a mark only has to check the lines through its cell
+struct BoardState {
+    marked: Vec<bool>,
+    row_hits: Vec<usize>,
+    col_hits: Vec<usize>,
+    diagonal_hits: usize,
+    anti_diagonal_hits: usize,
+    unmarked_sum: u64,
+    won: bool,
+}
 
-    let mut called_boards = IdxSet::new();
+impl BoardState {
+    fn new(board: &Board) -> Self {
+        Self {
+            marked: vec![false; board.cells.len()],
+            row_hits: vec![0; board.size],
+            col_hits: vec![0; board.size],
+            diagonal_hits: 0,
+            anti_diagonal_hits: 0,
+            // Can't overflow short of a board with billions of cells
+            unmarked_sum: board.cells.iter().map(|&num| u64::from(num)).sum(),
+            won: false,
+        }
+    }
 
-    for num in &subsystem.draw_order[4..] {
-        if called_boards.len() == subsystem.boards.len() {
-            break
+    // Returns the first line the mark completes, if any
+    fn mark(&mut self, board: &Board, cell: usize, rules: Rules) -> Option<Line> {
+        if self.marked[cell] {
+            return None;
+        }
+        self.marked[cell] = true;
+        self.unmarked_sum -= u64::from(board.cells[cell]);
+
+        let size = board.size;
+        let (row, col) = (cell / size, cell % size);
+        self.row_hits[row] += 1;
+        self.col_hits[col] += 1;
+        if row == col {
+            self.diagonal_hits += 1;
+        }
+        if row + col + 1 == size {
+            self.anti_diagonal_hits += 1;
         }
 
-        marked_nums.insert(*num);
-        for (index, board) in subsystem.boards.iter().enumerate() {
-            if called_boards.contains(&index) {
-                continue
-            }
+        if self.row_hits[row] == size {
+            Some(Line::Row(row))
+        } else if self.col_hits[col] == size {
+            Some(Line::Column(col))
+        } else if rules.diagonals && row == col && self.diagonal_hits == size {
+            Some(Line::Diagonal)
+        } else if rules.diagonals && row + col + 1 == size && self.anti_diagonal_hits == size {
+            Some(Line::AntiDiagonal)
+        } else {
+            None
+        }
+    }
+}
 
-            let winner = is_winner(board, &marked_nums);
-            if winner {
-                called_boards.insert(index);
-                winners.push(Winner {
-                    index,
-                    marked_nums: marked_nums.clone(),
-                    last_number: *num,
-                });
+pub struct Bingo<'a> {
+    boards: &'a [Board],
+    rules: Rules,
+    cells_by_number: HashMap<u32, Vec<(usize, usize)>>,
+    states: Vec<BoardState>,
+}
+
+impl<'a> Bingo<'a> {
+    pub fn new(boards: &'a [Board], rules: Rules) -> Self {
+        let mut cells_by_number: HashMap<u32, Vec<(usize, usize)>> = HashMap::new();
+        for (index, board) in boards.iter().enumerate() {
+            for (cell, num) in board.cells.iter().enumerate() {
+                cells_by_number.entry(*num).or_default().push((index, cell));
             }
         }
+
+        Self {
+            boards,
+            rules,
+            cells_by_number,
+            states: boards.iter().map(BoardState::new).collect(),
+        }
     }
 
-    winners.pop()
-}
+    // Boards that win on this draw, in board order; a board only ever wins once
+    pub fn draw(&mut self, draw: usize, number: u32) -> Vec<Win> {
+        let mut wins: Vec<Win> = Vec::new();
+        let Some(cells) = self.cells_by_number.get(&number) else {
+            return wins;
+        };
+
+        for &(index, cell) in cells {
+            let state = &mut self.states[index];
+            if state.won {
+                continue;
+            }
 
-fn is_winner(board: &Board, marked_nums: &NumSet) -> bool {
-    // Two different indices = two different ways to walk through the board (col by col, row by row)
-    let indices = (0..5).cartesian_product(0..5);
-    let reverse_indices = indices.clone().map(|(row, col)| (col, row)).collect();
+            if let Some(line) = state.mark(&self.boards[index], cell, self.rules) {
+                state.won = true;
+                wins.push(Win {
+                    board: index,
+                    draw,
+                    number,
+                    line,
+                    score: state.unmarked_sum.checked_mul(u64::from(number)),
+                });
+            }
+        }
 
-    let winner_found_walking_columns = check_winner(board, &indices.collect(), marked_nums);
-    let winner_found_walking_rows = check_winner(board, &reverse_indices, marked_nums);
-    winner_found_walking_columns || winner_found_walking_rows
+        wins.sort_by_key(|win| win.board);
+        wins
+    }
 }
 
-fn check_winner(board: &Board, indices: &Indices, marked_nums: &NumSet) -> bool {
-    let mut remaining_nums: NumSet = HashSet::new();
-    remaining_nums.extend(marked_nums.into_iter());
+fn play(boards: &[Board], draw_order: &[u32], rules: Rules) -> Timeline {
+    let mut bingo = Bingo::new(boards, rules);
+    let wins = draw_order
+        .iter()
+        .enumerate()
+        .flat_map(|(draw, &number)| bingo.draw(draw, number))
+        .collect::<Vec<Win>>();
 
-    for (iteration, (row, col)) in indices.iter().enumerate() {
-        remaining_nums.remove(&board[*row][*col]);
+    let never_won = (0..boards.len())
+        .filter(|&index| !bingo.states[index].won)
+        .collect();
 
-        if (iteration + 1) % 5 == 0 {
-            let found_bingo = marked_nums.len() - remaining_nums.len() >= 5;
-            if found_bingo {
-                return true;
-            }
+    Timeline { wins, never_won }
+}
 
-            remaining_nums.clear();
-            remaining_nums.extend(marked_nums.into_iter());
+// Draws one of the board's lines first and keeps the rest of the original order after it,
+// trying each line until the board is the only first winner
+fn rigged_draw_order(subsystem: &Subsystem, board: usize, rules: Rules) -> Option<Vec<u32>> {
+    let target = &subsystem.boards[board];
+    target.lines(rules).into_iter().find_map(|line| {
+        let mut draw_order: Vec<u32> = Vec::new();
+        for num in target.line_numbers(line) {
+            if !draw_order.contains(&num) {
+                draw_order.push(num);
+            }
         }
+        let rest = subsystem
+            .draw_order
+            .iter()
+            .filter(|num| !draw_order.contains(num))
+            .copied()
+            .collect::<Vec<u32>>();
+        draw_order.extend(rest);
+
+        let timeline = play(&subsystem.boards, &draw_order, rules);
+        let first_draw = timeline.wins.first()?.draw;
+        let mut first_winners = timeline
+            .wins
+            .iter()
+            .take_while(|win| win.draw == first_draw);
+        let only_target = first_winners.all(|win| win.board == board);
+        if only_target {
+            Some(draw_order)
+        } else {
+            None
+        }
+    })
+}
+
+fn format_score(win: Option<&Win>) -> Result<String, String> {
+    match win {
+        Some(win) => win
+            .score
+            .map(|score| score.to_string())
+            .ok_or(String::from("Winning score overflows a u64")),
+        None => Err(String::from("No board ever wins")),
     }
+}
 
-    false
+fn format_win_score(score: Option<u64>) -> String {
+    score.map_or(String::from("overflows a u64"), |score| score.to_string())
 }
 
-fn sum_unmarked_nums(board: &Board, marked_nums: &NumSet) -> u32 {
-    let mut unmarked_nums = NumSet::new();
-    unmarked_nums.extend(board.iter().flat_map(|row| row.iter()));
-    unmarked_nums.difference(marked_nums).sum()
+fn format_scores_report(wins: &[Win]) -> String {
+    let describe = |label: &str, win: Option<&Win>| match win {
+        Some(w) => format!(
+            "{}: board {} on {} ({}), score {}",
+            label,
+            w.board,
+            w.number,
+            w.line,
+            format_win_score(w.score)
+        ),
+        None => format!("{}: none", label),
+    };
+
+    format!(
+        "{}\n{}",
+        describe("first", wins.first()),
+        describe("last", wins.last())
+    )
 }
 
-fn compute_winner_score(possible_winner: &Option<Winner>, subsystem: &Subsystem) -> Option<u32> {
-    possible_winner.as_ref().map(|winner| {
-        let winning_board = subsystem.boards[winner.index];
-        let total_unmarked = sum_unmarked_nums(&winning_board, &winner.marked_nums);
-        total_unmarked * winner.last_number
-    })
+fn format_timeline_report(timeline: &Timeline) -> String {
+    let mut report = String::from("rank,board,draw,number,line,score");
+    for (rank, win) in timeline.wins.iter().enumerate() {
+        report += &format!(
+            "\n{},{},{},{},{},{}",
+            rank + 1,
+            win.board,
+            win.draw,
+            win.number,
+            win.line,
+            win.score.map_or(String::new(), |score| score.to_string())
+        );
+    }
+
+    let never_won = timeline.never_won.iter().map(|index| index.to_string());
+    report += &format!(
+        "\nnever won: {}",
+        never_won.collect::<Vec<String>>().join(",")
+    );
+    report
 }
 
 pub struct P4;
@@ -159,27 +338,53 @@ impl Puzzle<Subsystem> for P4 {
         4
     }
 
-    fn parse_data(&self, raw_data: &Vec<String>) -> Subsystem {
-        let draw_order = raw_data[0].split(',').map(|n| n.parse().unwrap()).collect();
-        let boards = read_boards(&raw_data[2..]);
-        Subsystem { draw_order, boards }
+    fn parse_data(&self, raw_data: &Vec<String>) -> Result<Subsystem, String> {
+        let (draw_line, board_lines) = raw_data.split_first().ok_or("Missing draw order")?;
+        let draw_order = read_draw_order(draw_line)?;
+        let boards = read_boards(board_lines)?;
+        Ok(Subsystem { draw_order, boards })
     }
 
-    fn solve_part_one(&self, subsystem: &Subsystem) {
-        let possible_winner = find_first_winner(subsystem);
-        let winner_score = compute_winner_score(&possible_winner, subsystem);
-        println!(
-            "{}",
-            winner_score.map_or(String::from("No solution"), |s| s.to_string())
-        );
+    fn solve_part_one(&self, subsystem: &Subsystem) -> Result<String, String> {
+        let timeline = play(&subsystem.boards, &subsystem.draw_order, Rules::default());
+        format_score(timeline.wins.first())
     }
 
-    fn solve_part_two(&self, subsystem: &Subsystem) {
-        let possible_winner = find_last_winner(subsystem);
-        let winner_score = compute_winner_score(&possible_winner, subsystem);
-        println!(
-            "{}",
-            winner_score.map_or(String::from("No solution"), |s| s.to_string())
-        );
+    fn solve_part_two(&self, subsystem: &Subsystem) -> Result<String, String> {
+        let timeline = play(&subsystem.boards, &subsystem.draw_order, Rules::default());
+        format_score(timeline.wins.last())
+    }
+
+    fn report(
+        &self,
+        subsystem: &Subsystem,
+        name: &str,
+        options: &ReportOptions,
+    ) -> Result<String, String> {
+        let rules = Rules {
+            diagonals: options.get("diagonals", false)?,
+        };
+
+        match name {
+            "scores" => {
+                let timeline = play(&subsystem.boards, &subsystem.draw_order, rules);
+                Ok(format_scores_report(&timeline.wins))
+            }
+            "timeline" => {
+                let timeline = play(&subsystem.boards, &subsystem.draw_order, rules);
+                Ok(format_timeline_report(&timeline))
+            }
+            "rig" => {
+                let board = options.get("board", 0usize)?;
+                if board >= subsystem.boards.len() {
+                    return Err(format!("There are only {} boards", subsystem.boards.len()));
+                }
+                let draw_order = rigged_draw_order(subsystem, board, rules)
+                    .ok_or(format!("No single line lets board {} win first", board))?;
+                let draw_order = draw_order.iter().map(|num| num.to_string());
+                Ok(draw_order.collect::<Vec<String>>().join(","))
+            }
+            _ => Err(format!("Puzzle 4 has no `{}` report", name)),
+        }
     }
 }
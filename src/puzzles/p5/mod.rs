@@ -1,20 +1,23 @@
-use itertools::Either;
-use std::iter;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::str::FromStr;
 
+use lazy_static::lazy_static;
+use num::integer::gcd;
 use regex::Regex;
 
 use crate::puzzles::puzzle::Puzzle;
+use crate::puzzles::report::{RenderFormat, ReportOptions};
+use crate::utils::ppm::{self, Rgb};
 
-#[derive(Clone)]
-pub struct Position {
-    x: i16,
-    y: i16,
+lazy_static! {
+    static ref LINE_REGEX: Regex =
+        Regex::new(r"^(?P<x0>-?\d+),(?P<y0>-?\d+) -> (?P<x1>-?\d+),(?P<y1>-?\d+)$").unwrap();
 }
 
-pub enum Coordinate {
-    X,
-    Y,
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Position {
+    x: i32,
+    y: i32,
 }
 
 #[derive(Clone)]
@@ -34,117 +37,17 @@ impl LineSegment {
         self.end.y == self.start.y
     }
 
-    pub fn is_diagonal(&self) -> bool {
-        !self.is_vertical_or_horizontal()
-    }
-
     pub fn is_vertical_or_horizontal(&self) -> bool {
         self.is_vertical() || self.is_horizontal()
     }
-
-    pub fn build_walk_list(&self) -> Vec<Position> {
-        if self.is_diagonal() {
-            self.build_diagonal_walk_list()
-        } else {
-            self.build_straight_walk_list()
-        }
-    }
-
-    fn build_diagonal_walk_list(&self) -> Vec<Position> {
-        let xs = if self.start.x <= self.end.x {
-            Either::Left(self.start.x..=self.end.x)
-        } else {
-            Either::Right((self.end.x..=self.start.x).rev())
-        };
-
-        let ys = if self.start.y <= self.end.y {
-            Either::Left(self.start.y..=self.end.y)
-        } else {
-            Either::Right((self.end.y..=self.start.y).rev())
-        };
-
-        xs.zip(ys).map(|(x, y)| Position { x, y }).collect()
-    }
-
-    fn build_straight_walk_list(&self) -> Vec<Position> {
-        let x = Coordinate::X;
-        let y = Coordinate::Y;
-
-        let min_x = LineSegment::min_coord_for_line(&self, &x);
-        let min_y = LineSegment::min_coord_for_line(&self, &y);
-        let max_x = LineSegment::max_coord_for_line(&self, &x);
-        let max_y = LineSegment::max_coord_for_line(&self, &y);
-
-        let along_x = min_x..=max_x;
-        let along_y = min_y..=max_y;
-        let is_vertical = self.is_vertical();
-        let (shorter, longer) = if is_vertical {
-            (along_x, along_y)
-        } else {
-            (along_y, along_x)
-        };
-
-        longer
-            .into_iter()
-            .zip(iter::repeat(shorter.start()))
-            .map(|(x, y)| {
-                if is_vertical {
-                    Position { x: *y, y: x }
-                } else {
-                    Position { x, y: *y }
-                }
-            })
-            .collect::<Vec<Position>>()
-    }
-
-    pub fn max_coord_for_lines(lines: &Vec<LineSegment>, coord: &Coordinate) -> i16 {
-        lines.into_iter().fold(i16::MIN, |max, line| {
-            let cur_max_coord = max;
-            let max_coord = LineSegment::max_coord_for_line(line, &coord);
-            if max_coord >= cur_max_coord {
-                max_coord
-            } else {
-                cur_max_coord
-            }
-        })
-    }
-
-    fn min_coord_for_line(line: &LineSegment, coord: &Coordinate) -> i16 {
-        let is_x_coord = matches!(coord, Coordinate::X);
-        let start = if is_x_coord {
-            line.start.x
-        } else {
-            line.start.y
-        };
-        let end = if is_x_coord { line.end.x } else { line.end.y };
-        if start <= end {
-            start
-        } else {
-            end
-        }
-    }
-
-    fn max_coord_for_line(line: &LineSegment, coord: &Coordinate) -> i16 {
-        let is_x_coord = matches!(coord, Coordinate::X);
-        let start = if is_x_coord {
-            line.start.x
-        } else {
-            line.start.y
-        };
-        let end = if is_x_coord { line.end.x } else { line.end.y };
-        if start >= end {
-            start
-        } else {
-            end
-        }
-    }
 }
 
 impl FromStr for LineSegment {
     type Err = LineSegmentParseErr;
     fn from_str(line_str: &str) -> Result<Self, Self::Err> {
-        let line_regex = Regex::new(r"(?P<x0>\d+),(?P<y0>\d+) -> (?P<x1>\d+),(?P<y1>\d+)").unwrap();
-        let captures = line_regex.captures(line_str).unwrap();
+        let captures = LINE_REGEX
+            .captures(line_str.trim())
+            .ok_or("Unable to parse line segment")?;
         let maybe_start = extract_position(&captures, 0);
         let maybe_end = extract_position(&captures, 1);
 
@@ -152,7 +55,7 @@ impl FromStr for LineSegment {
     }
 }
 
-fn parse_match_as_int(m: regex::Match) -> Result<i16, LineSegmentParseErr> {
+fn parse_match_as_int(m: regex::Match) -> Result<i32, LineSegmentParseErr> {
     m.as_str().parse().or(Err("Unable to parse coordinate"))
 }
 
@@ -173,51 +76,395 @@ fn extract_position(
     maybe_x.and_then(|x| maybe_y.map(|y| Position { x, y }))
 }
 
-fn build_intersections_map(lines: &Vec<LineSegment>) -> Vec<Vec<u32>> {
-    let max_x = LineSegment::max_coord_for_lines(lines, &Coordinate::X);
-    let max_y = LineSegment::max_coord_for_lines(lines, &Coordinate::Y);
+// A segment as the `steps + 1` lattice points `origin + k * direction`. The direction is
+// reduced by its gcd and given a canonical sign, so segments on the same infinite line share it.
+struct Span {
+    origin: (i64, i64),
+    direction: (i64, i64),
+    steps: i64,
+}
+
+impl Span {
+    fn new(segment: &LineSegment) -> Self {
+        let (x0, y0) = (i64::from(segment.start.x), i64::from(segment.start.y));
+        let (x1, y1) = (i64::from(segment.end.x), i64::from(segment.end.y));
+        let steps = gcd(x1 - x0, y1 - y0);
+        if steps == 0 {
+            // A single point lies on a line in every direction, so any will do
+            return Span {
+                origin: (x0, y0),
+                direction: (1, 0),
+                steps,
+            };
+        }
+
+        let (a, b) = ((x1 - x0) / steps, (y1 - y0) / steps);
+        if a > 0 || a == 0 && b > 0 {
+            Span {
+                origin: (x0, y0),
+                direction: (a, b),
+                steps,
+            }
+        } else {
+            Span {
+                origin: (x1, y1),
+                direction: (-a, -b),
+                steps,
+            }
+        }
+    }
+
+    fn point(&self, step: i64) -> Position {
+        let ((x, y), (a, b)) = (self.origin, self.direction);
+        Position {
+            x: (x + step * a) as i32,
+            y: (y + step * b) as i32,
+        }
+    }
+
+    // Identifies the infinite line the span lies on
+    fn line(&self) -> (i64, i64, i128) {
+        let ((x, y), (a, b)) = (self.origin, self.direction);
+        (
+            a,
+            b,
+            i128::from(b) * i128::from(x) - i128::from(a) * i128::from(y),
+        )
+    }
+
+    // Position along the line, which grows by `spacing` from one lattice point to the next
+    fn offset(&self) -> i128 {
+        let ((x, y), (a, b)) = (self.origin, self.direction);
+        i128::from(a) * i128::from(x) + i128::from(b) * i128::from(y)
+    }
+
+    fn spacing(&self) -> i128 {
+        let (a, b) = self.direction;
+        i128::from(a) * i128::from(a) + i128::from(b) * i128::from(b)
+    }
+
+    // Solves origin + t * direction = other.origin + u * other.direction for whole t and u
+    // within both spans. Spans on parallel lines never meet; collinear ones are swept instead.
+    fn crossing(&self, other: &Span) -> Option<Position> {
+        let (a1, b1) = (i128::from(self.direction.0), i128::from(self.direction.1));
+        let (a2, b2) = (i128::from(other.direction.0), i128::from(other.direction.1));
+        let denominator = a1 * b2 - b1 * a2;
+        if denominator == 0 {
+            return None;
+        }
+
+        let dx = i128::from(other.origin.0) - i128::from(self.origin.0);
+        let dy = i128::from(other.origin.1) - i128::from(self.origin.1);
+        let (t, u) = (dx * b2 - dy * a2, dx * b1 - dy * a1);
+        if t % denominator != 0 || u % denominator != 0 {
+            return None;
+        }
+
+        let (t, u) = (t / denominator, u / denominator);
+        let within = |step: i128, span: &Span| (0..=i128::from(span.steps)).contains(&step);
+        if within(t, self) && within(u, other) {
+            Some(self.point(t as i64))
+        } else {
+            None
+        }
+    }
+
+    // Steps whose point lands inside the viewport, if any
+    fn steps_within(&self, viewport: &Viewport) -> Option<(i64, i64)> {
+        let ((x, y), (a, b)) = (self.origin, self.direction);
+        let (left, right) = (
+            i64::from(viewport.top_left.x),
+            i64::from(viewport.bottom_right.x),
+        );
+        let (top, bottom) = (
+            i64::from(viewport.top_left.y),
+            i64::from(viewport.bottom_right.y),
+        );
+
+        let (x_first, x_last) = steps_between(x, a, left, right)?;
+        let (y_first, y_last) = steps_between(y, b, top, bottom)?;
+        let (first, last) = (
+            x_first.max(y_first).max(0),
+            x_last.min(y_last).min(self.steps),
+        );
+        (first <= last).then_some((first, last))
+    }
+}
+
+// Steps k for which start + k * step lies in min..=max
+fn steps_between(start: i64, step: i64, min: i64, max: i64) -> Option<(i64, i64)> {
+    if step == 0 {
+        return (min..=max).contains(&start).then_some((i64::MIN, i64::MAX));
+    }
 
-    let cols: usize = (max_x + 1).try_into().unwrap();
-    let rows: usize = (max_y + 1).try_into().unwrap();
+    let (low, high) = if step > 0 {
+        (min - start, max - start)
+    } else {
+        (start - max, start - min)
+    };
+    let step = step.abs();
+    Some((-(-low).div_euclid(step), high.div_euclid(step)))
+}
 
-    let mut intersections_map = vec![vec![0; cols]; rows];
-    for line in lines {
-        let walk_list = line.build_walk_list();
-        for pos in walk_list {
-            let x: usize = pos.x.try_into().unwrap();
-            let y: usize = pos.y.try_into().unwrap();
-            intersections_map[y][x] += 1;
+// Coverage along one line only changes where a span starts or ends, so it's swept as
+// intervals however long the spans are
+fn sweep_line(spans: &[&Span], histogram: &mut BTreeMap<u32, u64>) {
+    let spacing = spans[0].spacing();
+    let mut events = spans
+        .iter()
+        .flat_map(|span| {
+            let start = span.offset();
+            [
+                (start, 1),
+                (start + spacing * i128::from(span.steps + 1), -1),
+            ]
+        })
+        .collect::<Vec<(i128, i32)>>();
+    events.sort_unstable();
+
+    let mut coverage = 0;
+    for pair in events.windows(2) {
+        let ((offset, change), (next_offset, _)) = (pair[0], pair[1]);
+        coverage += change;
+        if coverage > 0 && next_offset > offset {
+            let points = ((next_offset - offset) / spacing) as u64;
+            *histogram.entry(coverage as u32).or_insert(0) += points;
         }
     }
+}
 
-    intersections_map
+// Only counts are kept, never individual points: collinear spans are swept per line, and
+// points where different lines cross are found pairwise and corrected afterwards
+pub struct OverlapMap {
+    spans: Vec<Span>,
+    histogram: BTreeMap<u32, u64>,
 }
 
-fn _print_intersections_map(intersections_map: &Vec<Vec<u32>>) {
-    let mut row_str = String::new();
-    for row in intersections_map {
-        row_str.clear();
-        for col in row {
-            row_str += &format!(
-                "{} ",
-                if *col == 0 {
-                    String::from(".")
-                } else {
-                    col.to_string()
+impl OverlapMap {
+    pub fn from_lines<'a>(lines: impl Iterator<Item = &'a LineSegment>) -> Self {
+        let spans = lines.map(Span::new).collect::<Vec<Span>>();
+
+        let mut line_ids = HashMap::new();
+        let line_of = spans
+            .iter()
+            .map(|span| {
+                let next_id = line_ids.len();
+                *line_ids.entry(span.line()).or_insert(next_id)
+            })
+            .collect::<Vec<usize>>();
+
+        let mut histogram = BTreeMap::new();
+        for line in 0..line_ids.len() {
+            let on_line = spans
+                .iter()
+                .zip(&line_of)
+                .filter(|(_, &span_line)| span_line == line)
+                .map(|(span, _)| span)
+                .collect::<Vec<&Span>>();
+            sweep_line(&on_line, &mut histogram);
+        }
+
+        // Spans covering each point where different lines meet
+        let mut crossings: HashMap<Position, BTreeSet<usize>> = HashMap::new();
+        for (i, span) in spans.iter().enumerate() {
+            for (j, other) in spans.iter().enumerate().skip(i + 1) {
+                if line_of[i] == line_of[j] {
+                    continue;
+                }
+                if let Some(pos) = span.crossing(other) {
+                    crossings.entry(pos).or_default().extend([i, j]);
                 }
+            }
+        }
+
+        // Each line's sweep counted the point with only its own spans, so move it to the total
+        for covering in crossings.values() {
+            let mut per_line: HashMap<usize, u32> = HashMap::new();
+            for &span in covering {
+                *per_line.entry(line_of[span]).or_insert(0) += 1;
+            }
+            for count in per_line.values() {
+                if let Some(points) = histogram.get_mut(count) {
+                    *points -= 1;
+                }
+            }
+            *histogram.entry(covering.len() as u32).or_insert(0) += 1;
+        }
+        histogram.retain(|_, points| *points > 0);
+
+        Self { spans, histogram }
+    }
+
+    pub fn count_overlapping_points(&self) -> u64 {
+        self.histogram.range(2..).map(|(_, points)| points).sum()
+    }
+
+    pub fn count_covered_points(&self) -> u64 {
+        self.histogram.values().sum()
+    }
+
+    // How many points are covered by exactly n lines, for every n that occurs
+    pub fn histogram(&self) -> &BTreeMap<u32, u64> {
+        &self.histogram
+    }
+
+    // Smallest box holding every covered point, as (top left, bottom right)
+    pub fn bounds(&self) -> Option<(Position, Position)> {
+        let mut ends = self
+            .spans
+            .iter()
+            .flat_map(|span| [span.point(0), span.point(span.steps)]);
+        let first = ends.next()?;
+        Some(ends.fold((first, first), |(min, max), pos| {
+            (
+                Position {
+                    x: min.x.min(pos.x),
+                    y: min.y.min(pos.y),
+                },
+                Position {
+                    x: max.x.max(pos.x),
+                    y: max.y.max(pos.y),
+                },
             )
-            .to_string();
+        }))
+    }
+
+    // Lines covering each cell of the viewport, row by row. Only the part of each span
+    // inside the viewport is visited.
+    pub fn coverage(&self, viewport: &Viewport) -> Vec<Vec<u32>> {
+        let (width, height) = (viewport.width() as usize, viewport.height() as usize);
+        let mut grid = vec![vec![0; width]; height];
+        for span in &self.spans {
+            let Some((first, last)) = span.steps_within(viewport) else {
+                continue;
+            };
+            for step in first..=last {
+                let pos = span.point(step);
+                let row = (i64::from(pos.y) - i64::from(viewport.top_left.y)) as usize;
+                let col = (i64::from(pos.x) - i64::from(viewport.top_left.x)) as usize;
+                grid[row][col] += 1;
+            }
+        }
+
+        grid
+    }
+}
+
+// Rendering fills in every cell of the viewport, one character or `scale` squared pixels
+// each, so refuse anything unreasonably large
+const MAX_VIEWPORT_PIXELS: i64 = 4_000_000;
+
+// Overlap count 0 is black, higher counts reuse the last colour
+const HEATMAP_COLOURS: [Rgb; 6] = [
+    (0, 0, 0),
+    (40, 80, 160),
+    (60, 160, 90),
+    (230, 200, 50),
+    (230, 120, 40),
+    (200, 40, 40),
+];
+
+pub struct Viewport {
+    top_left: Position,
+    bottom_right: Position,
+}
+
+impl Viewport {
+    fn from_corners(corners: &[i32]) -> Result<Self, String> {
+        match corners {
+            [x0, y0, x1, y1] if x0 <= x1 && y0 <= y1 => Ok(Viewport {
+                top_left: Position { x: *x0, y: *y0 },
+                bottom_right: Position { x: *x1, y: *y1 },
+            }),
+            _ => Err(String::from(
+                "Viewport looks like x0,y0,x1,y1 with x0 <= x1 and y0 <= y1",
+            )),
+        }
+    }
+
+    fn width(&self) -> i64 {
+        i64::from(self.bottom_right.x) - i64::from(self.top_left.x) + 1
+    }
+
+    fn height(&self) -> i64 {
+        i64::from(self.bottom_right.y) - i64::from(self.top_left.y) + 1
+    }
+}
+
+fn render_ascii(coverage: &[Vec<u32>]) -> String {
+    coverage
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|&count| match count {
+                    0 => '.',
+                    1..=9 => char::from_digit(count, 10).unwrap(),
+                    _ => '+',
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn heatmap_colour(&count: &u32) -> Rgb {
+    HEATMAP_COLOURS[(count as usize).min(HEATMAP_COLOURS.len() - 1)]
+}
+
+fn render_map(map: &OverlapMap, options: &ReportOptions) -> Result<String, String> {
+    let corners = options.get_list::<i32>("viewport", Vec::new())?;
+    let viewport = if corners.is_empty() {
+        let (top_left, bottom_right) = map.bounds().ok_or("There are no lines to render")?;
+        Viewport {
+            top_left,
+            bottom_right,
         }
-        println!("{}", row_str);
+    } else {
+        Viewport::from_corners(&corners)?
+    };
+
+    let format = options.get_render_format("ascii", 1)?;
+    let scale = match format {
+        RenderFormat::Text => 1,
+        RenderFormat::Ppm { scale } => scale as i64,
+    };
+    let pixels = viewport
+        .width()
+        .saturating_mul(viewport.height())
+        .saturating_mul(scale.saturating_mul(scale));
+    if pixels > MAX_VIEWPORT_PIXELS {
+        return Err(String::from(
+            "Viewport is too large, crop it with viewport=x0,y0,x1,y1 or lower the scale",
+        ));
+    }
+
+    let coverage = map.coverage(&viewport);
+    match format {
+        RenderFormat::Text => Ok(render_ascii(&coverage)),
+        RenderFormat::Ppm { scale } => ppm::render(&coverage, scale, heatmap_colour),
     }
 }
 
-fn count_overlapping_points(intersections_map: &Vec<Vec<u32>>) -> usize {
-    intersections_map
-        .into_iter()
-        .flatten()
-        .filter(|&n| n >= &2)
-        .count()
+fn select_lines(
+    lines: &[LineSegment],
+    include_diagonals: bool,
+) -> impl Iterator<Item = &LineSegment> {
+    lines
+        .iter()
+        .filter(move |line| include_diagonals || line.is_vertical_or_horizontal())
+}
+
+fn format_overlaps_report(map: &OverlapMap) -> String {
+    let mut report = format!(
+        "points covered: {}\noverlapping points: {}\nlines,points",
+        map.count_covered_points(),
+        map.count_overlapping_points()
+    );
+    for (count, points) in map.histogram() {
+        report += &format!("\n{},{}", count, points);
+    }
+
+    report
 }
 
 pub struct P5;
@@ -226,27 +473,154 @@ impl Puzzle<Vec<LineSegment>> for P5 {
         5
     }
 
-    fn parse_data(&self, raw_data: &Vec<String>) -> Vec<LineSegment> {
+    fn parse_data(&self, raw_data: &Vec<String>) -> Result<Vec<LineSegment>, String> {
         raw_data
             .iter()
-            .map(|line| LineSegment::from_str(line.as_str()).unwrap())
+            .map(|line| LineSegment::from_str(line.as_str()).map_err(String::from))
             .collect()
     }
 
-    fn solve_part_one(&self, lines: &Vec<LineSegment>) {
-        let vert_or_horiz_lines = lines
-            .iter()
-            .filter(|l| l.is_vertical_or_horizontal())
-            .cloned()
-            .collect::<Vec<LineSegment>>();
-        let intersections_map = build_intersections_map(&vert_or_horiz_lines);
-        let overlapping_points = count_overlapping_points(&intersections_map);
-        println!("{:?}", overlapping_points);
-    }
-
-    fn solve_part_two(&self, lines: &Vec<LineSegment>) {
-        let intersections_map = build_intersections_map(&lines);
-        let overlapping_points = count_overlapping_points(&intersections_map);
-        println!("{:?}", overlapping_points);
+    fn solve_part_one(&self, lines: &Vec<LineSegment>) -> Result<String, String> {
+        let overlap_map = OverlapMap::from_lines(select_lines(lines, false));
+        Ok(overlap_map.count_overlapping_points().to_string())
+    }
+
+    fn solve_part_two(&self, lines: &Vec<LineSegment>) -> Result<String, String> {
+        let overlap_map = OverlapMap::from_lines(select_lines(lines, true));
+        Ok(overlap_map.count_overlapping_points().to_string())
+    }
+
+    fn report(
+        &self,
+        lines: &Vec<LineSegment>,
+        name: &str,
+        options: &ReportOptions,
+    ) -> Result<String, String> {
+        let include_diagonals = options.get("diagonals", true)?;
+
+        match name {
+            "overlaps" => {
+                let overlap_map = OverlapMap::from_lines(select_lines(lines, include_diagonals));
+                Ok(format_overlaps_report(&overlap_map))
+            }
+            "map" => {
+                let overlap_map = OverlapMap::from_lines(select_lines(lines, include_diagonals));
+                render_map(&overlap_map, options)
+            }
+            _ => Err(format!("Puzzle 5 has no `{}` report", name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn segment((x0, y0, x1, y1): (i32, i32, i32, i32)) -> LineSegment {
+        LineSegment {
+            start: Position { x: x0, y: y0 },
+            end: Position { x: x1, y: y1 },
+        }
+    }
+
+    // Walks every lattice point of every segment and counts them one by one
+    fn brute_force(lines: &[LineSegment]) -> BTreeMap<u32, u64> {
+        let mut counts: HashMap<(i32, i32), u32> = HashMap::new();
+        for line in lines {
+            let (dx, dy) = (line.end.x - line.start.x, line.end.y - line.start.y);
+            let steps = gcd(dx, dy);
+            let (a, b) = if steps == 0 {
+                (0, 0)
+            } else {
+                (dx / steps, dy / steps)
+            };
+            for step in 0..=steps {
+                let point = (line.start.x + step * a, line.start.y + step * b);
+                *counts.entry(point).or_insert(0) += 1;
+            }
+        }
+
+        let mut histogram = BTreeMap::new();
+        for count in counts.values() {
+            *histogram.entry(*count).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    fn matches_brute_force(ends: &[(i32, i32, i32, i32)]) {
+        let lines = ends.iter().copied().map(segment).collect::<Vec<_>>();
+        let map = OverlapMap::from_lines(lines.iter());
+        assert_eq!(map.histogram(), &brute_force(&lines), "{:?}", ends);
+    }
+
+    #[test]
+    fn collinear_spans() {
+        // Overlapping in opposite directions, touching end to end, and nested
+        matches_brute_force(&[(0, 0, 6, 0), (8, 0, 3, 0), (8, 0, 10, 0), (4, 0, 5, 0)]);
+        matches_brute_force(&[(0, 0, 6, 6), (6, 6, 2, 2), (-3, -3, 0, 0)]);
+        matches_brute_force(&[(0, 0, 6, 3), (2, 1, 10, 5), (12, 6, 10, 5)]);
+        // Same line, with lattice points two cells apart
+        matches_brute_force(&[(0, 0, 4, 2), (6, 3, 2, 1)]);
+    }
+
+    #[test]
+    fn point_segments() {
+        matches_brute_force(&[(3, 3, 3, 3)]);
+        matches_brute_force(&[(3, 3, 3, 3), (3, 3, 3, 3), (4, 4, 4, 4)]);
+        matches_brute_force(&[(0, 0, 6, 0), (3, 0, 3, 0), (6, 0, 6, 0), (3, 1, 3, 1)]);
+        matches_brute_force(&[(0, 0, 4, 2), (2, 1, 2, 1), (1, 1, 1, 1), (0, 2, 4, 0)]);
+    }
+
+    #[test]
+    fn general_slopes() {
+        // Crossing at (2, 1), then crossing between lattice points at (3, 1.5)
+        matches_brute_force(&[(0, 0, 4, 2), (0, 2, 4, 0)]);
+        matches_brute_force(&[(0, 0, 6, 3), (0, 3, 6, 0)]);
+        // Three lines through (3, 2), two of them covered twice there
+        matches_brute_force(&[
+            (0, 0, 6, 4),
+            (3, 0, 3, 5),
+            (0, 2, 6, 2),
+            (1, 2, 4, 2),
+            (9, 6, 3, 2),
+        ]);
+        // Ending exactly on another line, and just missing it
+        matches_brute_force(&[(0, 0, 9, 3), (3, 1, 3, 8), (7, 0, 7, 2)]);
+    }
+
+    fn ends() -> impl Strategy<Value = (i32, i32, i32, i32)> {
+        let coordinate = -8..8;
+        (
+            coordinate.clone(),
+            coordinate.clone(),
+            coordinate.clone(),
+            coordinate,
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn any_segments(ends in prop::collection::vec(ends(), 0..10)) {
+            matches_brute_force(&ends);
+        }
+
+        // Only the slopes the puzzle itself uses, which overlap far more often
+        #[test]
+        fn axis_and_diagonal_segments(
+            starts in prop::collection::vec((-8..8, -8..8, 0..8, 0..4u8), 0..10)
+        ) {
+            let ends = starts
+                .into_iter()
+                .map(|(x, y, length, direction)| match direction {
+                    0 => (x, y, x + length, y),
+                    1 => (x, y, x, y + length),
+                    2 => (x, y, x + length, y + length),
+                    _ => (x, y, x + length, y - length),
+                })
+                .collect::<Vec<_>>();
+            matches_brute_force(&ends);
+        }
     }
 }
@@ -1,54 +1,349 @@
-use std::collections::HashMap;
+use num::traits::{CheckedAdd, CheckedMul, One, Zero};
+use num::BigUint;
 
 use crate::puzzles::puzzle::Puzzle;
-use crate::utils::input_file;
-
-fn count_fish(initial_timers: &Vec<u32>, days: usize) -> u64 {
-    // Hint from https://www.reddit.com/r/adventofcode/comments/rdfv7n/comment/ho0sqgs/?utm_source=share&utm_medium=web2x&context=3 to hold a dict of timers
-    let initial_histogram: HashMap<_, _> =
-        initial_timers.into_iter().fold(HashMap::new(), |mut h, t| {
-            h.entry(*t).and_modify(|c| *c += 1).or_insert(1u64);
-            h
-        });
+use crate::puzzles::report::ReportOptions;
+
+type Matrix<T> = Vec<Vec<T>>;
+
+// The transition matrix has states² entries and squaring it takes states³ steps
+const MAX_STATES: usize = 64;
+
+// Each day of a series is a line of output, and big counts can gain a digit every few days
+const MAX_SERIES_DAYS: usize = 10_000;
+const MAX_BIG_DAYS: u64 = 10_000;
+
+// Fish spawn every `cycle` days, newborns wait `newborn_delay` extra days before their first
+// cycle, and with a lifespan a fish dies instead of resetting once it has spawned that many times
+pub struct PopulationModel {
+    cycle: usize,
+    newborn_delay: usize,
+    lifespan: Option<usize>,
+}
+
+impl PopulationModel {
+    pub const LANTERNFISH: PopulationModel = PopulationModel {
+        cycle: 7,
+        newborn_delay: 2,
+        lifespan: None,
+    };
+
+    pub fn new(
+        cycle: usize,
+        newborn_delay: usize,
+        lifespan: Option<usize>,
+    ) -> Result<Self, String> {
+        if cycle == 0 {
+            return Err(String::from("Cycle must be at least 1 day"));
+        }
+        if lifespan == Some(0) {
+            return Err(String::from("Lifespan must be at least 1 cycle"));
+        }
+
+        // One state per timer value and generation
+        let states = cycle
+            .checked_add(newborn_delay)
+            .and_then(|timers| timers.checked_mul(lifespan.unwrap_or(1)));
+        if states.is_none_or(|states| states > MAX_STATES) {
+            return Err(format!(
+                "(cycle + delay) × lifespan must be at most {}",
+                MAX_STATES
+            ));
+        }
+
+        Ok(Self {
+            cycle,
+            newborn_delay,
+            lifespan,
+        })
+    }
+
+    fn max_timer(&self) -> usize {
+        self.cycle - 1 + self.newborn_delay
+    }
+
+    // Without a lifespan every fish behaves the same whatever its age
+    fn generations(&self) -> usize {
+        self.lifespan.unwrap_or(1)
+    }
+
+    // Fish sharing a timer and a spawn count behave identically, so each pair is one state
+    fn state_count(&self) -> usize {
+        (self.max_timer() + 1) * self.generations()
+    }
+
+    fn state(&self, timer: usize, generation: usize) -> usize {
+        generation * (self.max_timer() + 1) + timer
+    }
+
+    // Each fish in `from` becomes one fish in `to` the next day; fish at 0 appear twice
+    // (themselves and their newborn), or only once when they've reached their lifespan
+    fn transitions(&self) -> Vec<(usize, usize)> {
+        let mut transitions = Vec::new();
+        for generation in 0..self.generations() {
+            for timer in 1..=self.max_timer() {
+                let (from, to) = (
+                    self.state(timer, generation),
+                    self.state(timer - 1, generation),
+                );
+                transitions.push((from, to));
+            }
+
+            let spawning = self.state(0, generation);
+            transitions.push((spawning, self.state(self.max_timer(), 0)));
+            match self.lifespan {
+                Some(lifespan) if generation + 1 == lifespan => {}
+                Some(_) => transitions.push((spawning, self.state(self.cycle - 1, generation + 1))),
+                None => transitions.push((spawning, self.state(self.cycle - 1, generation))),
+            }
+        }
+
+        transitions
+    }
+
+    pub fn initial_histogram<T>(&self, initial_timers: &[usize]) -> Result<Vec<T>, String>
+    where
+        T: Zero + One + Clone + CheckedAdd,
+    {
+        let mut histogram = vec![T::zero(); self.state_count()];
+        for &timer in initial_timers {
+            if timer > self.max_timer() {
+                return Err(format!(
+                    "Timers must be 0 to {}, got {}",
+                    self.max_timer(),
+                    timer
+                ));
+            }
+            let state = self.state(timer, 0);
+            histogram[state] = histogram[state].checked_add(&T::one()).ok_or("Overflow")?;
+        }
+
+        Ok(histogram)
+    }
+
+    // None when a count doesn't fit in T
+    pub fn step<T: Zero + Clone + CheckedAdd>(&self, histogram: &[T]) -> Option<Vec<T>> {
+        let mut next = vec![T::zero(); histogram.len()];
+        for (from, to) in self.transitions() {
+            next[to] = next[to].checked_add(&histogram[from])?;
+        }
+        Some(next)
+    }
+
+    // next[i] = sum of matrix[i][j] * current[j]
+    fn transition_matrix<T: Zero + One + Clone + CheckedAdd>(&self) -> Matrix<T> {
+        let mut matrix = vec![vec![T::zero(); self.state_count()]; self.state_count()];
+        for (from, to) in self.transitions() {
+            matrix[to][from] = matrix[to][from].clone() + T::one();
+        }
+        matrix
+    }
+}
+
+fn identity<T: Zero + One + Clone>(size: usize) -> Matrix<T> {
+    let mut matrix = vec![vec![T::zero(); size]; size];
+    for (index, row) in matrix.iter_mut().enumerate() {
+        row[index] = T::one();
+    }
+    matrix
+}
 
-    let final_histogram = (1..=days).into_iter().fold(initial_histogram, |mut h, _| {
-        let new_fish = *h.entry(0).or_insert(0u64);
+// None when a value doesn't fit in T
+fn multiply<T>(a: &Matrix<T>, b: &Matrix<T>) -> Option<Matrix<T>>
+where
+    T: Zero + Clone + CheckedAdd + CheckedMul,
+{
+    a.iter()
+        .map(|row| {
+            (0..b[0].len())
+                .map(|col| {
+                    row.iter()
+                        .zip(b)
+                        .try_fold(T::zero(), |sum, (value, b_row)| {
+                            sum.checked_add(&value.checked_mul(&b_row[col])?)
+                        })
+                })
+                .collect()
+        })
+        .collect()
+}
 
-        for n in 1..=8 {
-            let fish_in_n = *h.entry(n).or_insert(0u64);
-            h.entry(n).and_modify(|c| *c -= fish_in_n).or_insert(0u64);
-            h.entry(n - 1)
-                .and_modify(|c| *c += fish_in_n)
-                .or_insert(0u64);
+// Square and multiply, so a billion days takes about thirty squarings
+fn matrix_power<T>(matrix: &Matrix<T>, mut exponent: u64) -> Option<Matrix<T>>
+where
+    T: Zero + One + Clone + CheckedAdd + CheckedMul,
+{
+    let mut result = identity(matrix.len());
+    let mut base = matrix.clone();
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = multiply(&result, &base)?;
         }
+        exponent >>= 1;
+        if exponent > 0 {
+            base = multiply(&base, &base)?;
+        }
+    }
 
-        h.entry(0).and_modify(|c| *c -= new_fish).or_insert(0u64);
-        h.entry(6).and_modify(|c| *c += new_fish).or_insert(0u64);
-        h.entry(8).and_modify(|c| *c += new_fish).or_insert(0u64);
+    Some(result)
+}
 
-        h
+fn count_fish<T>(
+    model: &PopulationModel,
+    initial_timers: &[usize],
+    days: u64,
+) -> Result<Option<T>, String>
+where
+    T: Zero + One + Clone + CheckedAdd + CheckedMul,
+{
+    let histogram = model.initial_histogram::<T>(initial_timers)?;
+    let count = matrix_power(&model.transition_matrix::<T>(), days).and_then(|matrix| {
+        matrix
+            .iter()
+            .flat_map(|row| row.iter().zip(&histogram))
+            .try_fold(T::zero(), |sum, (value, count)| {
+                sum.checked_add(&value.checked_mul(count)?)
+            })
     });
 
-    final_histogram.values().sum()
+    Ok(count)
+}
+
+// Total population at the start and after every day, stepping the histogram directly
+fn population_series<T>(
+    model: &PopulationModel,
+    initial_timers: &[usize],
+    days: usize,
+) -> Result<Vec<Option<T>>, String>
+where
+    T: Zero + One + Clone + CheckedAdd,
+{
+    let mut histogram = Some(model.initial_histogram::<T>(initial_timers)?);
+    let mut series = Vec::with_capacity(days + 1);
+    for day in 0..=days {
+        if day > 0 {
+            histogram = histogram.and_then(|h| model.step(&h));
+        }
+        let total = histogram.as_ref().and_then(|h| {
+            h.iter()
+                .try_fold(T::zero(), |sum, count| sum.checked_add(count))
+        });
+        series.push(total);
+    }
+
+    Ok(series)
+}
+
+fn format_count<T: ToString>(count: Option<T>) -> String {
+    count.map_or(String::from("Overflow"), |c| c.to_string())
+}
+
+fn format_series<T: ToString>(series: Vec<Option<T>>) -> String {
+    let mut report = String::from("day,population");
+    for (day, total) in series.into_iter().enumerate() {
+        report += &format!("\n{},{}", day, format_count(total));
+    }
+    report
+}
+
+fn model_from_options(options: &ReportOptions) -> Result<PopulationModel, String> {
+    let defaults = &PopulationModel::LANTERNFISH;
+    PopulationModel::new(
+        options.get("cycle", defaults.cycle)?,
+        options.get("delay", defaults.newborn_delay)?,
+        options.get_optional("lifespan")?,
+    )
+}
+
+fn solve_lanternfish(initial_timers: &[usize], days: u64) -> Result<String, String> {
+    let count = count_fish::<u64>(&PopulationModel::LANTERNFISH, initial_timers, days)?;
+    count.map(|count| count.to_string()).ok_or(format!(
+        "Population overflows a u64 after {} days, the `population` report takes big=true",
+        days
+    ))
 }
 
 pub struct P6;
-impl Puzzle<Vec<u32>> for P6 {
+impl Puzzle<Vec<usize>> for P6 {
     fn number(&self) -> u8 {
         6
     }
 
-    fn parse_data(&self, raw_data: &Vec<String>) -> Vec<u32> {
-        input_file::as_unsigned_ints_from_line(&raw_data[0])
+    fn parse_data(&self, raw_data: &Vec<String>) -> Result<Vec<usize>, String> {
+        let line = raw_data.first().ok_or("Missing timers")?;
+        line.split(',')
+            .map(|timer| {
+                timer
+                    .trim()
+                    .parse::<usize>()
+                    .or(Err(format!("Unable to parse timer `{}`", timer)))
+            })
+            .collect()
     }
 
-    fn solve_part_one(&self, initial_timers: &Vec<u32>) {
-        let count = count_fish(initial_timers, 80);
-        println!("{}", count);
+    fn solve_part_one(&self, initial_timers: &Vec<usize>) -> Result<String, String> {
+        solve_lanternfish(initial_timers, 80)
     }
 
-    fn solve_part_two(&self, initial_timers: &Vec<u32>) {
-        let count = count_fish(initial_timers, 256);
-        println!("{}", count);
+    fn solve_part_two(&self, initial_timers: &Vec<usize>) -> Result<String, String> {
+        solve_lanternfish(initial_timers, 256)
+    }
+
+    fn report(
+        &self,
+        initial_timers: &Vec<usize>,
+        name: &str,
+        options: &ReportOptions,
+    ) -> Result<String, String> {
+        let model = model_from_options(options)?;
+        let big = options.get("big", false)?;
+
+        match name {
+            "population" => {
+                let days = options.get("days", 256u64)?;
+                if big && days > MAX_BIG_DAYS {
+                    return Err(format!(
+                        "Big populations can run for at most {} days",
+                        MAX_BIG_DAYS
+                    ));
+                }
+                if big {
+                    Ok(format_count(count_fish::<BigUint>(
+                        &model,
+                        initial_timers,
+                        days,
+                    )?))
+                } else {
+                    Ok(format_count(count_fish::<u64>(
+                        &model,
+                        initial_timers,
+                        days,
+                    )?))
+                }
+            }
+            "series" => {
+                let days = options.get("days", 80usize)?;
+                if days > MAX_SERIES_DAYS {
+                    return Err(format!(
+                        "Series can run for at most {} days",
+                        MAX_SERIES_DAYS
+                    ));
+                }
+                if big {
+                    Ok(format_series(population_series::<BigUint>(
+                        &model,
+                        initial_timers,
+                        days,
+                    )?))
+                } else {
+                    Ok(format_series(population_series::<u64>(
+                        &model,
+                        initial_timers,
+                        days,
+                    )?))
+                }
+            }
+            _ => Err(format!("Puzzle 6 has no `{}` report", name)),
+        }
     }
 }
@@ -1,41 +1,267 @@
-use std::cmp;
-
 use crate::puzzles::puzzle::Puzzle;
+use crate::puzzles::report::ReportOptions;
 use crate::utils::input_file;
 
-enum FuelCostMethod {
-    Delta,
-    SumDelta,
-}
-
-fn build_cost_array(positions: &Vec<u32>, fuel_cost_method: FuelCostMethod) -> Vec<Vec<usize>> {
-    // Min is 0, but here for sanity
-    let min_pos = positions.iter().min().map(|m| *m as usize).unwrap();
-    let max_pos = positions.iter().max().map(|m| *m as usize).unwrap();
-    let min_max_diff = max_pos - min_pos;
-    let num_pos = positions.len();
-
-    let mut cost_array = vec![vec![0; num_pos]; min_max_diff + 1];
-    for row in min_pos..=max_pos {
-        for col in 0..num_pos {
-            let pos = positions[col] as usize;
-            let delta = if pos >= row { pos - row } else { row - pos };
-            let fuel_cost = if matches!(fuel_cost_method, FuelCostMethod::SumDelta) {
-                delta * (delta + 1) / 2
-            } else {
-                delta
-            };
-
-            cost_array[row][col] = fuel_cost;
+// Fuel a crab spends to move `delta` steps, or None if it doesn't fit a u64. Costs that are
+// convex and non-decreasing in the distance make the total convex in the target, which lets
+// the solver search instead of scan.
+pub trait FuelCost {
+    fn fuel_for(&self, crab: usize, delta: u64) -> Option<u64>;
+
+    // Searching a non-convex cost can settle on the wrong minimum, so costs opt in
+    fn is_convex(&self) -> bool {
+        false
+    }
+
+    // Costs with a known optimum narrow the search down to a few candidate positions
+    fn closed_form(&self, _positions: &[u32]) -> Option<Vec<u32>> {
+        None
+    }
+}
+
+pub struct Linear;
+impl FuelCost for Linear {
+    fn fuel_for(&self, _crab: usize, delta: u64) -> Option<u64> {
+        Some(delta)
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+
+    // The median minimises the sum of distances
+    fn closed_form(&self, positions: &[u32]) -> Option<Vec<u32>> {
+        let mut sorted = positions.to_vec();
+        let middle = sorted.len().checked_sub(1)? / 2;
+        let (_, &mut median, _) = sorted.select_nth_unstable(middle);
+        Some(vec![median])
+    }
+}
+
+pub struct Triangular;
+impl FuelCost for Triangular {
+    // Halving whichever factor is even first keeps the product from overflowing early
+    fn fuel_for(&self, _crab: usize, delta: u64) -> Option<u64> {
+        let next = delta.checked_add(1)?;
+        if delta.is_multiple_of(2) {
+            (delta / 2).checked_mul(next)
+        } else {
+            delta.checked_mul(next / 2)
         }
     }
-    cost_array
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+
+    // Triangular cost is within half a step of the mean's optimum, so its neighbours cover it
+    fn closed_form(&self, positions: &[u32]) -> Option<Vec<u32>> {
+        let sum = positions.iter().map(|&pos| u64::from(pos)).sum::<u64>();
+        let mean = sum.checked_div(positions.len() as u64)? as u32;
+        Some((mean.saturating_sub(1)..=mean.saturating_add(1)).collect())
+    }
 }
 
-fn compute_min_fuel(cost_array: Vec<Vec<usize>>) -> usize {
-    cost_array.into_iter().fold(usize::MAX, |cur_min, deltas| {
-        cmp::min(cur_min, deltas.iter().sum())
-    })
+pub struct Quadratic;
+impl FuelCost for Quadratic {
+    fn fuel_for(&self, _crab: usize, delta: u64) -> Option<u64> {
+        delta.checked_mul(delta)
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+// No crab ever spends more than the cap, which flattens the cost and breaks convexity
+pub struct Capped {
+    cost: Box<dyn FuelCost>,
+    cap: u64,
+}
+
+impl Capped {
+    pub fn new(cost: Box<dyn FuelCost>, cap: u64) -> Self {
+        Capped { cost, cap }
+    }
+}
+
+impl FuelCost for Capped {
+    // Anything too large for a u64 is over the cap anyway
+    fn fuel_for(&self, crab: usize, delta: u64) -> Option<u64> {
+        let fuel = self.cost.fuel_for(crab, delta);
+        Some(fuel.map_or(self.cap, |fuel| fuel.min(self.cap)))
+    }
+}
+
+// One multiplier per crab, in input order; crabs past the end of the list count once
+pub struct Weighted {
+    cost: Box<dyn FuelCost>,
+    weights: Vec<u64>,
+}
+
+impl Weighted {
+    pub fn new(cost: Box<dyn FuelCost>, weights: Vec<u64>) -> Self {
+        Weighted { cost, weights }
+    }
+}
+
+impl FuelCost for Weighted {
+    fn fuel_for(&self, crab: usize, delta: u64) -> Option<u64> {
+        let fuel = self.cost.fuel_for(crab, delta)?;
+        let weight = self.weights.get(crab).copied().unwrap_or(1);
+        weight.checked_mul(fuel)
+    }
+
+    fn is_convex(&self) -> bool {
+        self.cost.is_convex()
+    }
+}
+
+pub struct Alignment {
+    position: u32,
+    fuel: u64,
+}
+
+impl Alignment {
+    pub fn position(&self) -> u32 {
+        self.position
+    }
+
+    pub fn fuel(&self) -> u64 {
+        self.fuel
+    }
+}
+
+type AlignmentErr = String;
+
+fn total_fuel(
+    positions: &[u32],
+    target: u32,
+    cost: &(impl FuelCost + ?Sized),
+) -> Result<u64, AlignmentErr> {
+    positions
+        .iter()
+        .enumerate()
+        .try_fold(0u64, |total, (crab, &pos)| {
+            let fuel = cost.fuel_for(crab, u64::from(pos.abs_diff(target)))?;
+            total.checked_add(fuel)
+        })
+        .ok_or(format!("Fuel to align at {} overflows a u64", target))
+}
+
+// Cheapest of the candidates, preferring the lowest position on ties
+fn best_of(
+    positions: &[u32],
+    candidates: impl Iterator<Item = u32>,
+    cost: &(impl FuelCost + ?Sized),
+) -> Result<Alignment, AlignmentErr> {
+    let mut best: Option<Alignment> = None;
+    for position in candidates {
+        let fuel = total_fuel(positions, position, cost)?;
+        if best
+            .as_ref()
+            .is_none_or(|best| (fuel, position) < (best.fuel, best.position))
+        {
+            best = Some(Alignment { position, fuel });
+        }
+    }
+
+    best.ok_or(String::from("No positions to align at"))
+}
+
+fn position_range(positions: &[u32]) -> Result<(u32, u32), AlignmentErr> {
+    let low = positions.iter().min().ok_or("No crab positions")?;
+    let high = positions.iter().max().ok_or("No crab positions")?;
+    Ok((*low, *high))
+}
+
+// Ternary search over the crabs' span for any convex cost. Equal probes bracket the minimum,
+// but its plateau may reach further left, so only the right end moves in to keep the lowest
+// position on ties.
+pub fn align_convex(
+    positions: &[u32],
+    cost: &(impl FuelCost + ?Sized),
+) -> Result<Alignment, AlignmentErr> {
+    let (mut low, mut high) = position_range(positions)?;
+
+    while high - low > 2 {
+        let third = (high - low) / 3;
+        let (left, right) = (low + third, high - third);
+        let left_fuel = total_fuel(positions, left, cost)?;
+        let right_fuel = total_fuel(positions, right, cost)?;
+
+        if left_fuel < right_fuel {
+            high = right - 1;
+        } else if left_fuel > right_fuel {
+            low = left + 1;
+        } else {
+            high = right;
+        }
+    }
+
+    best_of(positions, low..=high, cost)
+}
+
+// Tries every position in the crabs' span, so works for any cost
+pub fn align_scan(
+    positions: &[u32],
+    cost: &(impl FuelCost + ?Sized),
+) -> Result<Alignment, AlignmentErr> {
+    let (low, high) = position_range(positions)?;
+    best_of(positions, low..=high, cost)
+}
+
+// The cheapest position and its fuel, using the fastest solver the cost allows
+pub fn align(
+    positions: &[u32],
+    cost: &(impl FuelCost + ?Sized),
+) -> Result<Alignment, AlignmentErr> {
+    match cost.closed_form(positions) {
+        Some(candidates) => best_of(positions, candidates.into_iter(), cost),
+        None if cost.is_convex() => align_convex(positions, cost),
+        None => align_scan(positions, cost),
+    }
+}
+
+fn format_cost_curve(positions: &[u32], cost: &dyn FuelCost) -> Result<String, String> {
+    let (low, high) = position_range(positions)?;
+    let mut report = String::from("position,fuel");
+    for position in low..=high {
+        report += &format!("\n{},{}", position, total_fuel(positions, position, cost)?);
+    }
+    Ok(report)
+}
+
+// cost=delta|sum-delta|quadratic, optionally wrapped by weights=w0,w1,... and then cap=N
+fn cost_from_options(
+    positions: &[u32],
+    options: &ReportOptions,
+) -> Result<Box<dyn FuelCost>, String> {
+    let name = options.get("cost", String::from("sum-delta"))?;
+    let mut cost: Box<dyn FuelCost> = match name.as_str() {
+        "delta" => Box::new(Linear),
+        "sum-delta" => Box::new(Triangular),
+        "quadratic" => Box::new(Quadratic),
+        _ => return Err(format!("Unknown fuel cost: `{}`", name)),
+    };
+
+    let weights = options.get_list::<u64>("weights", Vec::new())?;
+    if !weights.is_empty() {
+        if weights.len() != positions.len() {
+            return Err(format!(
+                "Expected {} weights, got {}",
+                positions.len(),
+                weights.len()
+            ));
+        }
+        cost = Box::new(Weighted::new(cost, weights));
+    }
+
+    if let Some(cap) = options.get_optional("cap")? {
+        cost = Box::new(Capped::new(cost, cap));
+    }
+
+    Ok(cost)
 }
 
 pub struct P7;
@@ -44,19 +270,85 @@ impl Puzzle<Vec<u32>> for P7 {
         7
     }
 
-    fn parse_data(&self, raw_data: &Vec<String>) -> Vec<u32> {
-        input_file::as_unsigned_ints_from_line(&raw_data[0])
+    fn parse_data(&self, raw_data: &Vec<String>) -> Result<Vec<u32>, String> {
+        let line = raw_data.first().ok_or("Missing positions")?;
+        input_file::as_unsigned_ints_from_line(line)
     }
 
-    fn solve_part_one(&self, positions: &Vec<u32>) {
-        let cost_array = build_cost_array(positions, FuelCostMethod::Delta);
-        let min_fuel = compute_min_fuel(cost_array);
-        println!("{}", min_fuel);
+    fn solve_part_one(&self, positions: &Vec<u32>) -> Result<String, String> {
+        Ok(align(positions, &Linear)?.fuel.to_string())
     }
 
-    fn solve_part_two(&self, positions: &Vec<u32>) {
-        let cost_array = build_cost_array(positions, FuelCostMethod::SumDelta);
-        let min_fuel = compute_min_fuel(cost_array);
-        println!("{}", min_fuel);
+    fn solve_part_two(&self, positions: &Vec<u32>) -> Result<String, String> {
+        Ok(align(positions, &Triangular)?.fuel.to_string())
+    }
+
+    fn report(
+        &self,
+        positions: &Vec<u32>,
+        name: &str,
+        options: &ReportOptions,
+    ) -> Result<String, String> {
+        let cost = cost_from_options(positions, options)?;
+
+        match name {
+            "alignment" => {
+                let alignment = match options.get("solver", String::from("auto"))?.as_str() {
+                    "auto" => align(positions, cost.as_ref())?,
+                    "search" if cost.is_convex() => align_convex(positions, cost.as_ref())?,
+                    "search" => return Err(String::from("Search needs a convex fuel cost")),
+                    "scan" => align_scan(positions, cost.as_ref())?,
+                    solver => return Err(format!("Unknown solver: `{}`", solver)),
+                };
+                Ok(format!(
+                    "position: {}\nfuel: {}",
+                    alignment.position, alignment.fuel
+                ))
+            }
+            "curve" => format_cost_curve(positions, cost.as_ref()),
+            _ => Err(format!("Puzzle 7 has no `{}` report", name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solvers_agree(positions: &[u32], cost: &dyn FuelCost) {
+        let searched = align_convex(positions, cost).unwrap();
+        let scanned = align_scan(positions, cost).unwrap();
+        assert_eq!(
+            (searched.position, searched.fuel),
+            (scanned.position, scanned.fuel),
+            "{:?}",
+            positions
+        );
+    }
+
+    #[test]
+    fn search_keeps_the_lowest_position_on_ties() {
+        solvers_agree(&[0, 49, 36, 30, 38, 12], &Linear);
+        solvers_agree(&[0, 100], &Linear);
+        solvers_agree(&[3, 3, 90, 90], &Linear);
+        solvers_agree(&[0, 1], &Triangular);
+    }
+
+    #[test]
+    fn search_matches_scan() {
+        // Small deterministic LCG so the cases don't need a dependency
+        let mut seed = 12345u64;
+        let mut next = |bound: u64| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (seed >> 33) % bound
+        };
+
+        for _ in 0..200 {
+            let count = 1 + next(8) as usize;
+            let positions = (0..count).map(|_| next(60) as u32).collect::<Vec<u32>>();
+            solvers_agree(&positions, &Linear);
+            solvers_agree(&positions, &Triangular);
+            solvers_agree(&positions, &Quadratic);
+        }
     }
 }
@@ -1,55 +1,49 @@
-use std::cmp::Ordering;
-use std::collections::HashMap;
-use std::hash::{Hash, Hasher};
-use std::ops::Sub;
+use std::collections::HashSet;
+use std::fmt;
 use std::str::FromStr;
 
 use crate::puzzles::puzzle::Puzzle;
+use crate::puzzles::report::ReportOptions;
 
-type Decoder<'a> = HashMap<&'a Pattern, u8>;
-type Patterns = [Pattern; 10];
-type Output = [Pattern; 4];
+const SEGMENTS: usize = 7;
+const ALL_SEGMENTS: u8 = (1 << SEGMENTS) - 1;
 
-#[derive(Eq, Clone, Copy)]
-struct Pattern {
+// Segments lit for each digit on a correctly wired display, bit 0 is `a` up to bit 6 for `g`
+const DIGIT_SEGMENTS: [u8; 10] = [
+    0b111_0111, 0b010_0100, 0b101_1101, 0b110_1101, 0b010_1110, 0b110_1011, 0b111_1011, 0b010_0101,
+    0b111_1111, 0b110_1111,
+];
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Pattern {
     hash: u8,
     length: u8,
 }
 
 fn char_to_bit(chr: char) -> u8 {
     match chr {
-        'a' => 1 << 0,
-        'b' => 1 << 1,
-        'c' => 1 << 2,
-        'd' => 1 << 3,
-        'e' => 1 << 4,
-        'f' => 1 << 5,
-        'g' => 1 << 6,
+        'a'..='g' => 1 << (chr as u8 - b'a'),
         _ => 0,
     }
 }
 
-fn count_bits(hash: u8) -> u8 {
-    (0..8)
-        .fold((0, hash), |(bits, cur_hash), _| {
-            (if cur_hash & 1 == 1 { bits + 1 } else { bits }, cur_hash >> 1)
-        })
-        .0
-}
-
 impl Pattern {
-    pub fn empty() -> Self {
-        Pattern { hash: 0, length: 0 }
+    pub fn new(pattern_str: &str) -> Self {
+        let hash = pattern_str
+            .chars()
+            .fold(0, |hash, chr| hash | char_to_bit(chr));
+        Pattern::from_hash(hash)
     }
 
-    pub fn new(pattern_str: &str) -> Self {
-        let hash = Pattern::hash_pattern(pattern_str);
-        let length = count_bits(hash);
-        Pattern { hash, length }
+    fn from_hash(hash: u8) -> Self {
+        Pattern {
+            hash,
+            length: hash.count_ones() as u8,
+        }
     }
 
-    fn hash_pattern(pattern: &str) -> u8 {
-        pattern.chars().fold(0, |hash, chr| hash | char_to_bit(chr))
+    fn has_wire(&self, wire: usize) -> bool {
+        self.hash & (1 << wire) != 0
     }
 
     pub fn is_unique_digit(&self) -> bool {
@@ -60,46 +54,223 @@ impl Pattern {
     }
 }
 
-impl Sub<Pattern> for Pattern {
-    type Output = Pattern;
-
-    fn sub(self, rhs: Pattern) -> Pattern {
-        let new_hash = self.hash & !rhs.hash;
-        let new_length = count_bits(new_hash);
-        Pattern {
-            hash: new_hash,
-            length: new_length,
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for wire in (0..SEGMENTS).filter(|&wire| self.has_wire(wire)) {
+            write!(f, "{}", (b'a' + wire as u8) as char)?;
         }
+        Ok(())
     }
 }
 
-impl Hash for Pattern {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.hash.hash(state);
+// The segment each wire a-g actually drives, as a single bit
+pub struct Wiring {
+    segments: [u8; SEGMENTS],
+}
+
+impl Wiring {
+    fn translate(&self, pattern: &Pattern) -> u8 {
+        (0..SEGMENTS)
+            .filter(|&wire| pattern.has_wire(wire))
+            .fold(0, |hash, wire| hash | self.segments[wire])
+    }
+
+    pub fn digit_for(&self, pattern: &Pattern) -> Option<u8> {
+        let segments = self.translate(pattern);
+        DIGIT_SEGMENTS
+            .iter()
+            .position(|&digit| digit == segments)
+            .map(|digit| digit as u8)
+    }
+
+    // The wires that light the digit's segments under this wiring
+    pub fn encode_digit(&self, digit: u8) -> Pattern {
+        let segments = DIGIT_SEGMENTS[digit as usize];
+        let hash = (0..SEGMENTS)
+            .filter(|&wire| self.segments[wire] & segments != 0)
+            .fold(0, |hash, wire| hash | (1 << wire));
+        Pattern::from_hash(hash)
+    }
+
+    // Every pattern has to light a real digit, and the ten of them every digit once
+    fn fits(&self, patterns: &[Pattern]) -> bool {
+        let digits = patterns
+            .iter()
+            .map(|pattern| self.digit_for(pattern))
+            .collect::<Option<HashSet<u8>>>();
+        digits.is_some_and(|digits| digits.len() == DIGIT_SEGMENTS.len())
     }
 }
 
-impl Ord for Pattern {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.length.cmp(&other.length)
+// Written as the segments driven by wires a to g, e.g. `deafgbc`
+impl fmt::Display for Wiring {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for segment in self.segments {
+            write!(f, "{}", (b'a' + segment.trailing_zeros() as u8) as char)?;
+        }
+        Ok(())
     }
 }
 
-impl PartialOrd for Pattern {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.length.cmp(&other.length))
+impl FromStr for Wiring {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut segments = [0u8; SEGMENTS];
+        let mut count = 0;
+        for chr in s.chars() {
+            let slot = segments
+                .get_mut(count)
+                .ok_or("Wiring must name 7 segments")?;
+            *slot = char_to_bit(chr);
+            count += 1;
+        }
+
+        let all_used = segments.iter().fold(0, |used, segment| used | segment) == ALL_SEGMENTS;
+        if count == SEGMENTS && all_used {
+            Ok(Wiring { segments })
+        } else {
+            Err("Wiring must use each of a-g exactly once")
+        }
+    }
+}
+
+// u64::MAX has 20 digits, so anything wider would only be padding
+const MAX_ENCODED_DIGITS: usize = 20;
+
+// Scrambles a number the way a display with this wiring would: the ten digit patterns,
+// then one output pattern per digit, zero padded to `width`
+fn encode_note(number: u64, wiring: &Wiring, width: usize) -> String {
+    let patterns = (0..10).map(|digit| wiring.encode_digit(digit).to_string());
+    let digits = format!("{:0width$}", number, width = width);
+    let output = digits
+        .bytes()
+        .map(|digit| wiring.encode_digit(digit - b'0').to_string());
+
+    format!(
+        "{} | {}",
+        patterns.collect::<Vec<String>>().join(" "),
+        output.collect::<Vec<String>>().join(" ")
+    )
+}
+
+// Three text rows per digit, each cell drawn only when its segment is lit
+const RENDER_ROWS: [[Option<(u8, char)>; 3]; 3] = [
+    [None, Some((0, '_')), None],
+    [Some((1, '|')), Some((3, '_')), Some((2, '|'))],
+    [Some((4, '|')), Some((6, '_')), Some((5, '|'))],
+];
+
+fn render_segments(displays: &[u8]) -> String {
+    RENDER_ROWS
+        .iter()
+        .map(|row| {
+            displays
+                .iter()
+                .flat_map(|&segments| {
+                    row.iter().map(move |cell| match cell {
+                        Some((segment, chr)) if segments & (1 << segment) != 0 => *chr,
+                        _ => ' ',
+                    })
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+type Candidates = [u8; SEGMENTS];
+
+// Could this pattern be the digit, given the segments each wire might still drive?
+fn could_be_digit(pattern: &Pattern, digit: u8, candidates: &Candidates) -> bool {
+    pattern.length == digit.count_ones() as u8
+        && (0..SEGMENTS).all(|wire| {
+            let allowed = if pattern.has_wire(wire) {
+                digit
+            } else {
+                !digit & ALL_SEGMENTS
+            };
+            candidates[wire] & allowed != 0
+        })
+}
+
+// Narrows each wire's candidate segments until nothing changes. A pattern's wires can only
+// drive segments lit by one of the digits it could still be, and its unlit wires only segments
+// one of them leaves dark. False when some wire is left with nothing.
+fn propagate(patterns: &[Pattern], candidates: &mut Candidates) -> bool {
+    loop {
+        let before = *candidates;
+
+        for pattern in patterns {
+            let (lit, unlit) = DIGIT_SEGMENTS
+                .iter()
+                .filter(|&&digit| could_be_digit(pattern, digit, candidates))
+                .fold((0, 0), |(lit, unlit), &digit| {
+                    (lit | digit, unlit | (!digit & ALL_SEGMENTS))
+                });
+
+            for (wire, allowed) in candidates.iter_mut().enumerate() {
+                *allowed &= if pattern.has_wire(wire) { lit } else { unlit };
+            }
+        }
+
+        // A wire down to one segment rules that segment out for every other wire
+        for wire in 0..SEGMENTS {
+            if candidates[wire].count_ones() == 1 {
+                let settled = candidates[wire];
+                for (other, allowed) in candidates.iter_mut().enumerate() {
+                    if other != wire {
+                        *allowed &= !settled;
+                    }
+                }
+            }
+        }
+
+        if candidates.contains(&0) {
+            return false;
+        }
+        if *candidates == before {
+            return true;
+        }
     }
 }
 
-impl PartialEq for Pattern {
-    fn eq(&self, other: &Self) -> bool {
-        self.hash == other.hash
+// Propagates, then branches on the least certain wire. Stops once two wirings are found,
+// since that's already enough to call the entry ambiguous.
+fn find_wirings(patterns: &[Pattern], mut candidates: Candidates, wirings: &mut Vec<Wiring>) {
+    if wirings.len() > 1 || !propagate(patterns, &mut candidates) {
+        return;
+    }
+
+    let unsettled = (0..SEGMENTS)
+        .filter(|&wire| candidates[wire].count_ones() > 1)
+        .min_by_key(|&wire| candidates[wire].count_ones());
+
+    match unsettled {
+        None => {
+            let wiring = Wiring {
+                segments: candidates,
+            };
+            if wiring.fits(patterns) {
+                wirings.push(wiring);
+            }
+        }
+        Some(wire) => {
+            for segment in (0..SEGMENTS).map(|bit| 1 << bit) {
+                if candidates[wire] & segment != 0 {
+                    let mut guess = candidates;
+                    guess[wire] = segment;
+                    find_wirings(patterns, guess, wirings);
+                }
+            }
+        }
     }
 }
 
+type DecodeErr = &'static str;
+
 pub struct NoteEntry {
-    patterns: Patterns,
-    output: Output,
+    patterns: [Pattern; 10],
+    output: Vec<Pattern>,
 }
 
 impl NoteEntry {
@@ -107,109 +278,168 @@ impl NoteEntry {
         self.output
             .iter()
             .filter(|&pattern| pattern.is_unique_digit())
-            .count()
-            .try_into()
-            .unwrap()
-    }
-
-    pub fn get_output_value(&self) -> u64 {
-        let mut decoder = Decoder::new();
-
-        let pattern_for_1 = &self.patterns[0];
-        let pattern_for_7 = &self.patterns[1];
-        let pattern_for_4 = &self.patterns[2];
-        let pattern_for_8 = &self.patterns[9];
-        // Hint from https://www.reddit.com/r/adventofcode/comments/rbvpui/2021_day_8_part_2_my_logic_on_paper_i_used_python/?utm_source=share&utm_medium=web2x&context=3
-        // to use the L structure found diffing 1 from 4
-        let pattern_for_4_and_1 = *pattern_for_4 - *pattern_for_1;
-
-        decoder.insert(pattern_for_1, 1);
-        decoder.insert(pattern_for_7, 7);
-        decoder.insert(pattern_for_4, 4);
-        decoder.insert(pattern_for_8, 8);
-
-        // Deduce the 5 segmented numbers (2, 3, 5)
-        for index in 3..=5 {
-            let current = &self.patterns[index];
-            let includes_1 = (*current - *pattern_for_1).length == 3;
-            if includes_1 {
-                decoder.insert(current, 3);
-            } else {
-                let includes_4_and_1_diff = (*current - pattern_for_4_and_1).length == 3;
-                decoder.insert(current, if includes_4_and_1_diff { 5 } else { 2 });
-            }
-        }
+            .count() as u64
+    }
 
-        // Deduce the 6 segmented numbers (0, 6, 9)
-        for index in 6..=8 {
-            let current = &self.patterns[index];
-            let includes_4 = (*current - *pattern_for_4).length == 2;
-            if includes_4 {
-                decoder.insert(current, 9);
-            } else {
-                let includes_4_and_1_diff = (*current - pattern_for_4_and_1).length == 4;
-                decoder.insert(current, if includes_4_and_1_diff { 6 } else { 0 });
-            }
+    pub fn decode_wiring(&self) -> Result<Wiring, DecodeErr> {
+        let mut wirings = Vec::new();
+        find_wirings(&self.patterns, [ALL_SEGMENTS; SEGMENTS], &mut wirings);
+
+        match wirings.len() {
+            0 => Err("No wiring is consistent with the patterns"),
+            1 => Ok(wirings.remove(0)),
+            _ => Err("Patterns are ambiguous, more than one wiring fits"),
         }
+    }
 
+    pub fn decode_output(&self, wiring: &Wiring) -> Result<Vec<u8>, DecodeErr> {
         self.output
             .iter()
-            .fold((0u64, 4u64), |(value, exp), pattern| {
-                let power = u64::pow(10u64, (exp - 1).try_into().unwrap());
-                let base = decoder[pattern] as u64;
-                (value + base * power, exp - 1)
+            .map(|pattern| {
+                wiring
+                    .digit_for(pattern)
+                    .ok_or("Output pattern isn't a digit")
             })
-            .0
+            .collect()
     }
+
+    pub fn get_output_value(&self) -> Result<u64, DecodeErr> {
+        let digits = self.decode_output(&self.decode_wiring()?)?;
+        digits.iter().try_fold(0u64, |value, &digit| {
+            value
+                .checked_mul(10)
+                .and_then(|value| value.checked_add(u64::from(digit)))
+                .ok_or("Output value is too large")
+        })
+    }
+}
+
+type NoteEntryParseErr = &'static str;
+
+fn parse_pattern_list(s: &str) -> Result<Vec<Pattern>, NoteEntryParseErr> {
+    s.split_whitespace()
+        .map(|pattern_str| {
+            let valid_pattern = pattern_str.chars().all(|chr| ('a'..='g').contains(&chr));
+            if valid_pattern {
+                Ok(Pattern::new(pattern_str))
+            } else {
+                Err("Pattern has a segment outside a-g")
+            }
+        })
+        .collect()
 }
 
 impl FromStr for NoteEntry {
-    type Err = ();
+    type Err = NoteEntryParseErr;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let tokenized_str: Vec<&str> = s.split('|').collect();
-        let patterns_split = tokenized_str[0].trim().split(' ');
-        let mut patterns: Patterns = [Pattern::empty(); 10];
-        for (index, pattern) in patterns_split.into_iter().enumerate() {
-            patterns[index] = Pattern::new(pattern);
-        }
-        patterns.sort();
+        let (patterns_str, output_str) = s.split_once('|').ok_or("Missing `|` separator")?;
+
+        let patterns = parse_pattern_list(patterns_str)?
+            .try_into()
+            .or(Err("Expected exactly 10 patterns"))?;
 
-        let output_split = tokenized_str[1].trim().split(' ');
-        let mut output: Output = [Pattern::empty(); 4];
-        for (index, pattern) in output_split.into_iter().enumerate() {
-            output[index] = Pattern::new(pattern);
+        let output = parse_pattern_list(output_str)?;
+        if output.is_empty() {
+            return Err("Missing output patterns");
         }
 
         Ok(NoteEntry { patterns, output })
     }
 }
 
+fn format_decode_report(notes: &[NoteEntry]) -> String {
+    let mut report = String::from("entry,wiring,output");
+    for (index, entry) in notes.iter().enumerate() {
+        let decoded = entry.decode_wiring().and_then(|wiring| {
+            let digits = entry.decode_output(&wiring)?;
+            let digits = digits
+                .iter()
+                .map(|digit| digit.to_string())
+                .collect::<String>();
+            Ok(format!("{},{}", wiring, digits))
+        });
+        let decoded = decoded.unwrap_or_else(|err| format!("error: {}", err));
+        report += &format!("\n{},{}", index, decoded);
+    }
+
+    report
+}
+
+fn format_render_report(notes: &[NoteEntry]) -> String {
+    notes
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let rendered = entry.decode_wiring().map(|wiring| {
+                let displays = entry
+                    .output
+                    .iter()
+                    .map(|pattern| wiring.translate(pattern))
+                    .collect::<Vec<u8>>();
+                render_segments(&displays)
+            });
+            let rendered = rendered.unwrap_or_else(|err| format!("error: {}", err));
+            format!("entry {}\n{}", index, rendered)
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n")
+}
+
 pub struct P8;
 impl Puzzle<Vec<NoteEntry>> for P8 {
     fn number(&self) -> u8 {
         8
     }
 
-    fn parse_data(&self, raw_data: &Vec<String>) -> Vec<NoteEntry> {
+    fn parse_data(&self, raw_data: &Vec<String>) -> Result<Vec<NoteEntry>, String> {
         raw_data
-            .into_iter()
-            .map(|line| NoteEntry::from_str(&line[0..]).unwrap())
+            .iter()
+            .map(|line| NoteEntry::from_str(line).map_err(String::from))
             .collect()
     }
 
-    fn solve_part_one(&self, notes: &Vec<NoteEntry>) {
-        let count: u64 = notes
-            .into_iter()
-            .map(|entry| entry.count_unique_digits())
-            .sum();
-        println!("{}", count);
+    fn solve_part_one(&self, notes: &Vec<NoteEntry>) -> Result<String, String> {
+        let count: u64 = notes.iter().map(|entry| entry.count_unique_digits()).sum();
+        Ok(count.to_string())
     }
 
-    fn solve_part_two(&self, notes: &Vec<NoteEntry>) {
-        let count: u64 = notes
-            .into_iter()
-            .map(|entry| entry.get_output_value())
-            .sum();
-        println!("{}", count);
+    fn solve_part_two(&self, notes: &Vec<NoteEntry>) -> Result<String, String> {
+        let total = notes
+            .iter()
+            .enumerate()
+            .try_fold(0u64, |total, (index, entry)| {
+                let value = entry
+                    .get_output_value()
+                    .map_err(|err| format!("Entry {}: {}", index, err))?;
+                total
+                    .checked_add(value)
+                    .ok_or(String::from("Sum of output values is too large"))
+            })?;
+        Ok(total.to_string())
+    }
+
+    fn report(
+        &self,
+        notes: &Vec<NoteEntry>,
+        name: &str,
+        options: &ReportOptions,
+    ) -> Result<String, String> {
+        match name {
+            "decode" => Ok(format_decode_report(notes)),
+            "render" => Ok(format_render_report(notes)),
+            "encode" => {
+                let number = options.get("number", 0u64)?;
+                let wiring = Wiring::from_str(&options.get("wiring", String::from("abcdefg"))?)?;
+                let width = options.get("digits", 4usize)?;
+                if width > MAX_ENCODED_DIGITS {
+                    return Err(format!(
+                        "Notes can have at most {} digits",
+                        MAX_ENCODED_DIGITS
+                    ));
+                }
+                Ok(encode_note(number, &wiring, width))
+            }
+            _ => Err(format!("Puzzle 8 has no `{}` report", name)),
+        }
     }
 }
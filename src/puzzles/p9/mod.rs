@@ -1,149 +1,309 @@
-use std::collections::HashMap;
-use std::collections::HashSet;
-
 use crate::puzzles::puzzle::Puzzle;
-use crate::utils::input_file;
+use crate::puzzles::report::{RenderFormat, ReportOptions};
+use crate::utils::ppm::{self, Rgb};
 
 type HeightMap = Vec<Vec<u8>>;
 type Position = (usize, usize);
-type PathIncr = (isize, isize);
-struct Edge {
-    from: Position,
-    to: Position,
-}
-type AdjacencyList = Vec<Edge>;
-type AdjacencyGraph = HashMap<Position, Vec<Position>>;
-type PosSet = HashSet<Position>;
-
-fn build_adjacency_list(height_map: &HeightMap) -> AdjacencyList {
-    let mut adjacency_list = AdjacencyList::new();
-    let rows = height_map.len();
+
+const PEAK_HEIGHT: u8 = 9;
+
+fn neighbours(height_map: &HeightMap, (row, col): Position) -> impl Iterator<Item = Position> {
+    let (rows, cols) = (height_map.len(), height_map[0].len());
+    let up = row.checked_sub(1).map(|up| (up, col));
+    let down = (row + 1 < rows).then_some((row + 1, col));
+    let left = col.checked_sub(1).map(|left| (row, left));
+    let right = (col + 1 < cols).then_some((row, col + 1));
+    [up, down, left, right].into_iter().flatten()
+}
+
+fn positions(height_map: &HeightMap) -> impl Iterator<Item = Position> {
     let cols = height_map[0].len();
+    (0..height_map.len()).flat_map(move |row| (0..cols).map(move |col| (row, col)))
+}
 
-    for row in 0..rows {
-        for col in 0..cols {
-            let current = height_map[row][col];
-            let path_increments: Vec<PathIncr> = vec![(-1, 0), (1, 0), (0, -1), (0, 1)];
-            let adjacent_edges = path_increments
-                .into_iter()
-                .map(|(y_inc, x_inc)| {
-                    // Pushes us out of height map bounds
-                    if y_inc == -1 && row == 0
-                        || x_inc == -1 && col == 0
-                        || y_inc == 1 && row == rows - 1
-                        || x_inc == 1 && col == cols - 1
-                    {
-                        None
-                    } else {
-                        let next_row = ((row as isize) + y_inc) as usize;
-                        let next_col = ((col as isize) + x_inc) as usize;
-                        Some((next_row, next_col))
-                    }
-                })
-                // Keep only valid positions
-                .filter_map(|adj_pos| adj_pos)
-                .filter(|&(row, col)| {
-                    let adjacent = height_map[row][col];
-                    adjacent <= current
-                })
-                .map(|adjacent| Edge {
-                    from: (row, col),
-                    to: adjacent,
-                });
+// Strictly lower than every neighbour
+fn find_low_points(height_map: &HeightMap) -> Vec<Position> {
+    positions(height_map)
+        .filter(|&(row, col)| {
+            let height = height_map[row][col];
+            neighbours(height_map, (row, col)).all(|(r, c)| height_map[r][c] > height)
+        })
+        .collect()
+}
+
+fn sum_risk_levels(height_map: &HeightMap) -> u32 {
+    find_low_points(height_map)
+        .iter()
+        .map(|&(row, col)| (height_map[row][col] + 1) as u32)
+        .sum()
+}
+
+// Union by size with path halving, over cells numbered row by row
+struct DisjointSet {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(size: usize) -> Self {
+        Self {
+            parents: (0..size).collect(),
+            sizes: vec![1; size],
+        }
+    }
+
+    fn find(&mut self, mut cell: usize) -> usize {
+        while self.parents[cell] != cell {
+            self.parents[cell] = self.parents[self.parents[cell]];
+            cell = self.parents[cell];
+        }
+        cell
+    }
 
-            adjacency_list.extend(adjacent_edges);
+    fn union(&mut self, a: usize, b: usize) {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        if self.sizes[a] < self.sizes[b] {
+            std::mem::swap(&mut a, &mut b);
         }
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
     }
-    adjacency_list
 }
 
-fn build_inv_adjacency_graph(adjacency_list: &AdjacencyList, height_map: &HeightMap) -> AdjacencyGraph {
-    adjacency_list
-        .into_iter()
-        .fold(AdjacencyGraph::new(), |mut graph, edge| {
-            let Edge { from, to } = edge;
-            let low_enough = height_map[from.0][from.1] < 9;
-            if low_enough {
-                // Must be indirected (hence the redundant to -> from, from -> to)
-                let adjacents = graph.entry(*to).or_insert(Vec::new());
-                adjacents.push(*from);
+// Basins are areas walled off by peaks. An area holds one low point on any real heightmap,
+// but every low point it does hold is kept rather than guessing which one it belongs to.
+pub struct Basin {
+    size: usize,
+    low_points: Vec<Position>,
+}
 
-                let adjacents = graph.entry(*from).or_insert(Vec::new());
-                adjacents.push(*to);
-            }
+// Basin index for every cell, row by row; peaks and flat areas with no low point get None
+pub struct BasinMap {
+    cols: usize,
+    labels: Vec<Option<usize>>,
+    basins: Vec<Basin>,
+}
 
-            graph
-        })
+impl BasinMap {
+    pub fn label_at(&self, (row, col): Position) -> Option<usize> {
+        self.labels[row * self.cols + col]
+    }
+
+    pub fn unbasined_cells(&self) -> impl Iterator<Item = Position> + '_ {
+        self.labels
+            .iter()
+            .enumerate()
+            .filter(|(_, label)| label.is_none())
+            .map(|(cell, _)| (cell / self.cols, cell % self.cols))
+    }
 }
 
-fn build_positions_set(height_map: &HeightMap) -> PosSet {
-    let rows = height_map.len();
+// One pass joining each non-peak cell to its right and lower neighbours, then one more to
+// number the areas that contain a low point
+fn label_basins(height_map: &HeightMap) -> BasinMap {
     let cols = height_map[0].len();
+    let cell_index = |(row, col): Position| row * cols + col;
+    let is_peak = |(row, col): Position| height_map[row][col] >= PEAK_HEIGHT;
 
-    let mut indices = PosSet::new();
-    for row in 0..rows {
-        for col in 0..cols {
-            indices.insert((row, col));
+    let mut areas = DisjointSet::new(height_map.len() * cols);
+    for pos in positions(height_map).filter(|&pos| !is_peak(pos)) {
+        let (row, col) = pos;
+        for next in [(row + 1, col), (row, col + 1)] {
+            let in_bounds = next.0 < height_map.len() && next.1 < cols;
+            if in_bounds && !is_peak(next) {
+                areas.union(cell_index(pos), cell_index(next));
+            }
         }
     }
-    indices
-}
 
-fn find_sink_positions(height_map: &HeightMap, adjacency_list: &AdjacencyList) -> Vec<Position> {
-    let all_positions = build_positions_set(height_map);
-    let visited = PosSet::from_iter(adjacency_list.iter().map(|edge| edge.from));
-    all_positions.difference(&visited).map(|&pos| pos).collect()
+    let mut basin_for_area: Vec<Option<usize>> = vec![None; areas.parents.len()];
+    let mut basins: Vec<Basin> = Vec::new();
+    for low_point in find_low_points(height_map) {
+        let area = areas.find(cell_index(low_point));
+        match basin_for_area[area] {
+            Some(basin) => basins[basin].low_points.push(low_point),
+            None => {
+                basin_for_area[area] = Some(basins.len());
+                basins.push(Basin {
+                    size: areas.sizes[area],
+                    low_points: vec![low_point],
+                });
+            }
+        }
+    }
+
+    let labels = positions(height_map)
+        .map(|pos| {
+            if is_peak(pos) {
+                None
+            } else {
+                basin_for_area[areas.find(cell_index(pos))]
+            }
+        })
+        .collect();
+
+    BasinMap {
+        cols,
+        labels,
+        basins,
+    }
 }
 
-fn sum_risk_levels(height_map: &HeightMap) -> u32 {
-    let adjacency_list = build_adjacency_list(height_map);
-    let sinks = find_sink_positions(height_map, &adjacency_list);
-    sinks
+fn mul_largest_three_basins(height_map: &HeightMap) -> usize {
+    let mut sizes = label_basins(height_map)
+        .basins
         .iter()
-        .map(|&(row, col)| (height_map[row][col] + 1) as u32)
-        .sum()
+        .map(|basin| basin.size)
+        .collect::<Vec<usize>>();
+
+    // Descending order by num cells
+    sizes.sort_unstable_by(|a, b| b.cmp(a));
+    sizes.iter().take(3).product()
 }
 
-fn find_basins(sinks: &Vec<Position>, adjacency_graph: &AdjacencyGraph) -> Vec<PosSet> {
-    sinks
-        .iter()
-        .map(|sink| compute_basin(sink, adjacency_graph))
-        .collect()
+fn format_basins_report(basin_map: &BasinMap) -> String {
+    let mut report = format!(
+        "basins: {}\ncells in no basin: {}\nbasin,size,low points",
+        basin_map.basins.len(),
+        basin_map.unbasined_cells().count()
+    );
+    for (index, basin) in basin_map.basins.iter().enumerate() {
+        let low_points = basin
+            .low_points
+            .iter()
+            .map(|(row, col)| format!("({} {})", row, col))
+            .collect::<Vec<String>>();
+        report += &format!("\n{},{},{}", index, basin.size, low_points.join(" "));
+    }
+
+    report
 }
 
-// Depth-first search to get all the nodes from the sink
-fn compute_basin(sink: &Position, adjacency_graph: &AdjacencyGraph) -> PosSet {
-    let mut basin = PosSet::new();
-    let mut to_visit = adjacency_graph[sink].clone();
-    while !to_visit.is_empty() {
-        to_visit.pop().and_then(|next| {
-            let unvisited = !basin.contains(&next);
-            if unvisited {
-                basin.insert(next);
-                if adjacency_graph.contains_key(&next) {
-                    to_visit.extend(adjacency_graph[&next].clone());
-                }
-            }
+// Three largest basins in bright colours, the rest cycling through muted ones
+const TOP_BASIN_COLOURS: [Rgb; 3] = [(220, 50, 47), (38, 160, 70), (40, 110, 220)];
+const BASIN_COLOURS: [Rgb; 6] = [
+    (120, 100, 130),
+    (100, 125, 110),
+    (130, 115, 90),
+    (95, 110, 135),
+    (135, 100, 100),
+    (110, 130, 130),
+];
+const PEAK_COLOUR: Rgb = (0, 0, 0);
+const NO_BASIN_COLOUR: Rgb = (60, 60, 60);
+const LOW_POINT_COLOUR: Rgb = (255, 255, 255);
 
-            Some(())
-        });
-    }
+enum CellStyle {
+    Peak,
+    NoBasin,
+    Basin { colour: Rgb },
+    LowPoint { colour: Rgb },
+}
+
+fn basin_colours(basin_map: &BasinMap) -> Vec<Rgb> {
+    let mut by_size = (0..basin_map.basins.len()).collect::<Vec<usize>>();
+    by_size.sort_by_key(|&basin| std::cmp::Reverse(basin_map.basins[basin].size));
 
-    basin
+    let mut colours = (0..basin_map.basins.len())
+        .map(|basin| BASIN_COLOURS[basin % BASIN_COLOURS.len()])
+        .collect::<Vec<_>>();
+    for (&basin, &colour) in by_size.iter().zip(TOP_BASIN_COLOURS.iter()) {
+        colours[basin] = colour;
+    }
+    colours
 }
 
-fn mul_largest_three_basins(height_map: &HeightMap) -> u32 {
-    let adjacency_list = build_adjacency_list(height_map);
-    let adjacency_graph = build_inv_adjacency_graph(&adjacency_list, &height_map);
-    let sinks = find_sink_positions(height_map, &adjacency_list);
-    let mut basins = find_basins(&sinks, &adjacency_graph);
+fn cell_styles(height_map: &HeightMap, basin_map: &BasinMap) -> Vec<Vec<CellStyle>> {
+    let colours = basin_colours(basin_map);
+    let mut styles = height_map
+        .iter()
+        .enumerate()
+        .map(|(row, heights)| {
+            heights
+                .iter()
+                .enumerate()
+                .map(|(col, &height)| match basin_map.label_at((row, col)) {
+                    Some(basin) => CellStyle::Basin {
+                        colour: colours[basin],
+                    },
+                    None if height >= PEAK_HEIGHT => CellStyle::Peak,
+                    None => CellStyle::NoBasin,
+                })
+                .collect::<Vec<CellStyle>>()
+        })
+        .collect::<Vec<_>>();
+
+    for (basin, colour) in basin_map.basins.iter().zip(colours) {
+        for &(row, col) in &basin.low_points {
+            styles[row][col] = CellStyle::LowPoint { colour };
+        }
+    }
 
-    // Descending order by num nodes
-    basins.sort_by(|a, b| b.len().cmp(&a.len()));
+    styles
+}
 
-    basins[0..3]
+// Heights on a 24-bit background per basin; low points in bold white on their basin's colour
+fn render_ansi(height_map: &HeightMap, styles: &[Vec<CellStyle>]) -> String {
+    height_map
         .iter()
-        .fold(1u32, |acc, basin| acc * (basin.len() as u32))
+        .zip(styles)
+        .map(|(heights, row_styles)| {
+            let mut line = heights
+                .iter()
+                .zip(row_styles)
+                .map(|(height, style)| match style {
+                    CellStyle::Peak => format!("\x1b[0;2m{}", height),
+                    CellStyle::NoBasin => format!("\x1b[0m{}", height),
+                    CellStyle::Basin { colour: (r, g, b) } => {
+                        format!("\x1b[0;48;2;{};{};{}m{}", r, g, b, height)
+                    }
+                    CellStyle::LowPoint { colour: (r, g, b) } => {
+                        format!("\x1b[0;1;97;48;2;{};{};{}m{}", r, g, b, height)
+                    }
+                })
+                .collect::<String>();
+            line += "\x1b[0m";
+            line
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn style_colour(style: &CellStyle) -> Rgb {
+    match style {
+        CellStyle::Peak => PEAK_COLOUR,
+        CellStyle::NoBasin => NO_BASIN_COLOUR,
+        CellStyle::Basin { colour } => *colour,
+        CellStyle::LowPoint { .. } => LOW_POINT_COLOUR,
+    }
+}
+
+fn render_map(height_map: &HeightMap, options: &ReportOptions) -> Result<String, String> {
+    let styles = cell_styles(height_map, &label_basins(height_map));
+    match options.get_render_format("ansi", 4)? {
+        RenderFormat::Text => Ok(render_ansi(height_map, &styles)),
+        RenderFormat::Ppm { scale } => ppm::render(&styles, scale, style_colour),
+    }
+}
+
+// Basin index per cell as a CSV grid, blank for cells in no basin
+fn format_labels_report(height_map: &HeightMap, basin_map: &BasinMap) -> String {
+    (0..height_map.len())
+        .map(|row| {
+            (0..basin_map.cols)
+                .map(|col| {
+                    basin_map
+                        .label_at((row, col))
+                        .map_or(String::new(), |l| l.to_string())
+                })
+                .collect::<Vec<String>>()
+                .join(",")
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
 }
 
 pub struct P9;
@@ -152,20 +312,51 @@ impl Puzzle<HeightMap> for P9 {
         9
     }
 
-    fn parse_data(&self, raw_data: &Vec<String>) -> HeightMap {
-        raw_data
+    fn parse_data(&self, raw_data: &Vec<String>) -> Result<HeightMap, String> {
+        let height_map = raw_data
             .iter()
-            .map(|line| input_file::as_contig_unsigned_bytes(line))
-            .collect::<HeightMap>()
+            .map(|line| {
+                line.chars()
+                    .map(|chr| {
+                        chr.to_digit(10)
+                            .map(|height| height as u8)
+                            .ok_or(format!("Heights must be digits, got `{}`", chr))
+                    })
+                    .collect::<Result<Vec<u8>, String>>()
+            })
+            .collect::<Result<HeightMap, _>>()?;
+
+        let cols = height_map.first().map_or(0, |row| row.len());
+        if cols == 0 || height_map.iter().any(|row| row.len() != cols) {
+            return Err(String::from(
+                "Heightmap rows must all have the same, non-zero width",
+            ));
+        }
+
+        Ok(height_map)
     }
 
-    fn solve_part_one(&self, height_map: &HeightMap) {
+    fn solve_part_one(&self, height_map: &HeightMap) -> Result<String, String> {
         let sum_risk_levels = sum_risk_levels(height_map);
-        println!("{}", sum_risk_levels);
+        Ok(sum_risk_levels.to_string())
     }
 
-    fn solve_part_two(&self, height_map: &HeightMap) {
+    fn solve_part_two(&self, height_map: &HeightMap) -> Result<String, String> {
         let prod_largest_three_basins = mul_largest_three_basins(height_map);
-        println!("{}", prod_largest_three_basins);
+        Ok(prod_largest_three_basins.to_string())
+    }
+
+    fn report(
+        &self,
+        height_map: &HeightMap,
+        name: &str,
+        options: &ReportOptions,
+    ) -> Result<String, String> {
+        match name {
+            "basins" => Ok(format_basins_report(&label_basins(height_map))),
+            "labels" => Ok(format_labels_report(height_map, &label_basins(height_map))),
+            "map" => render_map(height_map, options),
+            _ => Err(format!("Puzzle 9 has no `{}` report", name)),
+        }
     }
 }
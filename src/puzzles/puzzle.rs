@@ -1,22 +1,86 @@
+use crate::puzzles::report::ReportOptions;
+#[cfg(feature = "fetch")]
+use crate::utils::fetch;
 use crate::utils::input_file;
+use crate::utils::mem_stats::measure;
+
+pub struct Answers {
+    pub part_one: String,
+    pub part_two: String,
+}
 
 pub trait Puzzle<T> {
     fn number(&self) -> u8;
-    fn solve_part_one(&self, data: &T);
-    fn solve_part_two(&self, data: &T);
+    fn solve_part_one(&self, data: &T) -> Result<String, String>;
+    fn solve_part_two(&self, data: &T) -> Result<String, String>;
 
-    fn read_input_file(&self, test_input: bool) -> Vec<String> {
+    fn read_input_file(&self, test_input: bool) -> Result<Vec<String>, String> {
         let number = self.number();
+
+        #[cfg(feature = "fetch")]
+        if !test_input
+            && !input_file::input_file_path(number, test_input).is_file()
+            && !fetch::offer_to_fetch(number)
+        {
+            return Err(format!("No input file for puzzle {}", number));
+        }
+
         input_file::read_lines(number, test_input)
     }
 
-    fn parse_data(&self, raw_data: &Vec<String>) -> T;
+    fn parse_data(&self, raw_data: &Vec<String>) -> Result<T, String>;
+
+    // Puzzles can expose extra analyses of their input beyond the two answers
+    fn report(&self, _data: &T, name: &str, _options: &ReportOptions) -> Result<String, String> {
+        Err(format!("Puzzle {} has no `{}` report", self.number(), name))
+    }
+
+    fn answers(&self, test_input: bool) -> Result<Answers, String> {
+        let number = self.number();
+        let raw_data = self.read_input_file(test_input)?;
+        let data = measure("parse", || self.parse_data(&raw_data))
+            .map_err(|err| format!("Couldn't parse input for puzzle {}: {}", number, err))?;
+
+        let part_one = measure("part one", || self.solve_part_one(&data))
+            .map_err(|err| format!("Couldn't solve puzzle {} part one: {}", number, err))?;
+        let part_two = measure("part two", || self.solve_part_two(&data))
+            .map_err(|err| format!("Couldn't solve puzzle {} part two: {}", number, err))?;
 
-    fn solve(&self, test_input: bool) {
-        let raw_data = self.read_input_file(test_input);
-        let data = self.parse_data(&raw_data);
+        Ok(Answers { part_one, part_two })
+    }
+
+    // Solves just one part, so submitting an answer doesn't wait on (or fail with) the other
+    fn answer(&self, test_input: bool, part: u8) -> Result<String, String> {
+        let number = self.number();
+        if !matches!(part, 1 | 2) {
+            return Err(format!("No part {}", part));
+        }
+
+        let raw_data = self.read_input_file(test_input)?;
+        let data = measure("parse", || self.parse_data(&raw_data))
+            .map_err(|err| format!("Couldn't parse input for puzzle {}: {}", number, err))?;
+
+        if part == 1 {
+            measure("part one", || self.solve_part_one(&data))
+                .map_err(|err| format!("Couldn't solve puzzle {} part one: {}", number, err))
+        } else {
+            measure("part two", || self.solve_part_two(&data))
+                .map_err(|err| format!("Couldn't solve puzzle {} part two: {}", number, err))
+        }
+    }
+
+    fn run_report(
+        &self,
+        test_input: bool,
+        name: &str,
+        options: &ReportOptions,
+    ) -> Result<String, String> {
+        let number = self.number();
+        let raw_data = self.read_input_file(test_input)?;
+        let data = self
+            .parse_data(&raw_data)
+            .map_err(|err| format!("Couldn't parse input for puzzle {}: {}", number, err))?;
 
-        self.solve_part_one(&data);
-        self.solve_part_two(&data);
+        self.report(&data, name, options)
     }
 }
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+type ReportOptionsErr = String;
+
+// Larger cells than this only blow up the image without showing anything more
+const MAX_SCALE: usize = 64;
+
+// How a map-like report draws itself: in its own text format, or as an image with
+// `scale` pixels per cell
+pub enum RenderFormat {
+    Text,
+    Ppm { scale: usize },
+}
+
+// key=value pairs passed after a report's name on the command line
+pub struct ReportOptions {
+    values: HashMap<String, String>,
+}
+
+impl ReportOptions {
+    pub fn parse(pairs: &[String]) -> Result<Self, ReportOptionsErr> {
+        let values = pairs
+            .iter()
+            .map(|pair| {
+                pair.split_once('=')
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .ok_or(format!(
+                        "Report options look like key=value, got `{}`",
+                        pair
+                    ))
+            })
+            .collect::<Result<HashMap<_, _>, _>>()?;
+
+        Ok(Self { values })
+    }
+
+    pub fn get_optional<V: FromStr>(&self, key: &str) -> Result<Option<V>, ReportOptionsErr> {
+        self.values
+            .get(key)
+            .map(|value| {
+                value
+                    .parse()
+                    .or(Err(format!("Invalid value for `{}`: `{}`", key, value)))
+            })
+            .transpose()
+    }
+
+    pub fn get<V: FromStr>(&self, key: &str, default: V) -> Result<V, ReportOptionsErr> {
+        self.get_optional(key).map(|value| value.unwrap_or(default))
+    }
+
+    pub fn get_list<V: FromStr>(
+        &self,
+        key: &str,
+        default: Vec<V>,
+    ) -> Result<Vec<V>, ReportOptionsErr> {
+        match self.values.get(key) {
+            Some(values) => values
+                .split(',')
+                .map(|value| {
+                    value
+                        .parse()
+                        .or(Err(format!("Invalid value for `{}`: `{}`", key, value)))
+                })
+                .collect(),
+            None => Ok(default),
+        }
+    }
+
+    pub fn get_render_format(
+        &self,
+        text_format: &str,
+        default_scale: usize,
+    ) -> Result<RenderFormat, ReportOptionsErr> {
+        match self.get("format", text_format.to_string())?.as_str() {
+            format if format == text_format => Ok(RenderFormat::Text),
+            "ppm" => {
+                let scale = self.get("scale", default_scale)?;
+                if scale == 0 || scale > MAX_SCALE {
+                    return Err(format!("Scale must be between 1 and {}", MAX_SCALE));
+                }
+                Ok(RenderFormat::Ppm { scale })
+            }
+            format => Err(format!("Unknown format: `{}`", format)),
+        }
+    }
+}
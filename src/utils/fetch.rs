@@ -0,0 +1,53 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::utils::input_file;
+use crate::utils::prompt;
+use crate::utils::session::Session;
+
+type FetchErr = String;
+
+// Inputs never change once published, so anything already on disk is used as-is.
+pub fn fetch_input(day: u8, session: &Session) -> Result<PathBuf, FetchErr> {
+    let path = input_file::input_file_path(day, false);
+    if path.is_file() {
+        return Ok(path);
+    }
+
+    let url = format!("{}/input", session.day_url(day));
+    let body = ureq::get(&url)
+        .set("Cookie", &session.cookie())
+        .set("User-Agent", &session.user_agent())
+        .call()
+        .map_err(|err| format!("Couldn't fetch input for puzzle {}: {}", day, err))?
+        .into_string()
+        .map_err(|err| format!("Couldn't read input for puzzle {}: {}", day, err))?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|err| format!("Couldn't create \"{}\": {}", dir.display(), err))?;
+    }
+
+    fs::write(&path, body)
+        .map_err(|err| format!("Couldn't write \"{}\": {}", path.display(), err))?;
+
+    Ok(path)
+}
+
+pub fn offer_to_fetch(day: u8) -> bool {
+    let question = format!("Input file for puzzle {} is missing. Fetch it?", day);
+    if !prompt::confirm(&question) {
+        return false;
+    }
+
+    match Session::load().and_then(|session| fetch_input(day, &session)) {
+        Ok(path) => {
+            println!("Saved input to \"{}\"", path.display());
+            true
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            false
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::utils::submit::Outcome;
+
+const DEFAULT_HISTORY_FILE: &str = ".aoc-history";
+const HISTORY_FILE_VAR: &str = "AOC_HISTORY_FILE";
+
+type HistoryErr = String;
+
+pub struct Attempt {
+    pub day: u8,
+    pub part: u8,
+    pub answer: String,
+    pub outcome: Outcome,
+}
+
+// One attempt per line, tab separated: day, part, answer, outcome
+impl FromStr for Attempt {
+    type Err = HistoryErr;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if let [day, part, answer, outcome] = fields[..] {
+            let day = day.parse().ok();
+            let part = part.parse().ok();
+            let outcome = Outcome::from_str(outcome).ok();
+            if let (Some(day), Some(part), Some(outcome)) = (day, part, outcome) {
+                return Ok(Attempt {
+                    day,
+                    part,
+                    answer: String::from(answer),
+                    outcome,
+                });
+            }
+        }
+
+        Err(format!("Couldn't parse attempt: `{}`", line))
+    }
+}
+
+impl Attempt {
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}",
+            self.day, self.part, self.answer, self.outcome
+        )
+    }
+}
+
+pub struct Bounds {
+    pub too_low: Option<i64>,
+    pub too_high: Option<i64>,
+}
+
+pub struct History {
+    path: PathBuf,
+    attempts: Vec<Attempt>,
+}
+
+impl History {
+    pub fn load() -> Result<Self, HistoryErr> {
+        let path =
+            PathBuf::from(env::var(HISTORY_FILE_VAR).unwrap_or(String::from(DEFAULT_HISTORY_FILE)));
+
+        let attempts = if path.is_file() {
+            fs::read_to_string(&path)
+                .map_err(|err| format!("Couldn't read \"{}\": {}", path.display(), err))?
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(Attempt::from_str)
+                .collect::<Result<Vec<_>, _>>()?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self { path, attempts })
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_attempts(attempts: Vec<Attempt>) -> Self {
+        Self {
+            path: PathBuf::new(),
+            attempts,
+        }
+    }
+
+    pub fn attempts_for(&self, day: u8, part: u8) -> impl Iterator<Item = &Attempt> {
+        self.attempts
+            .iter()
+            .filter(move |attempt| attempt.day == day && attempt.part == part)
+    }
+
+    pub fn find(&self, day: u8, part: u8, answer: &str) -> Option<&Attempt> {
+        self.attempts_for(day, part)
+            .filter(|attempt| attempt.answer == answer)
+            .find(|attempt| attempt.outcome.is_verdict())
+    }
+
+    pub fn solved(&self, day: u8, part: u8) -> Option<&Attempt> {
+        self.attempts_for(day, part)
+            .find(|attempt| matches!(attempt.outcome, Outcome::Correct))
+    }
+
+    // Tightest known bounds: the largest answer that was too low, the smallest that was too high
+    pub fn bounds(&self, day: u8, part: u8) -> Bounds {
+        self.attempts_for(day, part).fold(
+            Bounds {
+                too_low: None,
+                too_high: None,
+            },
+            |bounds, attempt| match (attempt.answer.parse::<i64>().ok(), &attempt.outcome) {
+                (Some(value), Outcome::TooLow) => Bounds {
+                    too_low: bounds.too_low.max(Some(value)),
+                    ..bounds
+                },
+                (Some(value), Outcome::TooHigh) => Bounds {
+                    too_high: Some(bounds.too_high.map_or(value, |high| high.min(value))),
+                    ..bounds
+                },
+                _ => bounds,
+            },
+        )
+    }
+
+    pub fn record(&mut self, attempt: Attempt) -> Result<(), HistoryErr> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|err| format!("Couldn't open \"{}\": {}", self.path.display(), err))?;
+
+        writeln!(file, "{}", attempt.to_line())
+            .map_err(|err| format!("Couldn't write \"{}\": {}", self.path.display(), err))?;
+
+        self.attempts.push(attempt);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(attempts: &[(u8, &str, Outcome)]) -> History {
+        History::with_attempts(
+            attempts
+                .iter()
+                .map(|&(part, answer, outcome)| Attempt {
+                    day: 1,
+                    part,
+                    answer: String::from(answer),
+                    outcome,
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn bounds_are_the_tightest_known() {
+        let history = history(&[
+            (1, "10", Outcome::TooLow),
+            (1, "90", Outcome::TooHigh),
+            (1, "30", Outcome::TooLow),
+            (1, "70", Outcome::TooHigh),
+            (1, "20", Outcome::TooLow),
+            (1, "80", Outcome::TooHigh),
+            (1, "50", Outcome::Incorrect),
+            (1, "abc", Outcome::TooLow),
+            (2, "60", Outcome::TooLow),
+        ]);
+
+        let bounds = history.bounds(1, 1);
+        assert_eq!((bounds.too_low, bounds.too_high), (Some(30), Some(70)));
+
+        let bounds = history.bounds(1, 2);
+        assert_eq!((bounds.too_low, bounds.too_high), (Some(60), None));
+
+        let bounds = history.bounds(2, 1);
+        assert_eq!((bounds.too_low, bounds.too_high), (None, None));
+    }
+
+    #[test]
+    fn attempts_round_trip_through_lines() {
+        let line = "3\t2\t-42\ttoo-low";
+        assert_eq!(Attempt::from_str(line).unwrap().to_line(), line);
+        assert!(Attempt::from_str("3\t2\t-42").is_err());
+        assert!(Attempt::from_str("3\t2\t-42\tmaybe").is_err());
+    }
+}
@@ -1,49 +1,59 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::path::Path;
-use std::result::Result::{Err, Ok};
+use std::path::{Path, PathBuf};
+use std::result::Result::Err;
 
-fn open_input_file(num: u8, test_input: bool) -> File {
+const INPUT_FILES_DIR: &str = "input-files/";
+
+pub fn input_file_path(num: u8, test_input: bool) -> PathBuf {
     let filename_suffix = if test_input { "-test.txt" } else { ".txt" };
     let filename = format!("{}{}", num, filename_suffix);
-    let path = Path::new("input-files/").join(Path::new(&filename));
-
-    match File::open(&path) {
-        Ok(file) => file,
-        Err(_) => {
-            let readable_path = path.display();
-            panic!(
-                "Input file for puzzle {} not found: attempted to read from \"{}\"",
-                num, readable_path
-            );
-        }
-    }
+    Path::new(INPUT_FILES_DIR).join(Path::new(&filename))
+}
+
+fn open_input_file(num: u8, test_input: bool) -> Result<File, String> {
+    let path = input_file_path(num, test_input);
+
+    File::open(&path).map_err(|_| {
+        format!(
+            "Input file for puzzle {} not found: attempted to read from \"{}\"",
+            num,
+            path.display()
+        )
+    })
 }
 
-pub fn read_lines(num: u8, test_input: bool) -> Vec<String> {
-    let file = open_input_file(num, test_input);
+pub fn read_lines(num: u8, test_input: bool) -> Result<Vec<String>, String> {
+    let file = open_input_file(num, test_input)?;
     BufReader::new(file)
         .lines()
-        .map(|line| line.expect("Could not parse line"))
+        .map(|line| line.map_err(|err| format!("Couldn't read input for puzzle {}: {}", num, err)))
         .collect()
 }
 
 // Common parsers here
-pub fn as_unsigned_short(item: &String) -> u16 {
-    match item.parse::<u16>() {
-        Ok(num) => num,
-        Err(_) => panic!("Couldn't parse {} as unsigned short", item),
-    }
+pub fn as_unsigned_int(item: &String) -> Result<u32, String> {
+    item.trim()
+        .parse::<u32>()
+        .or(Err(format!("Couldn't parse `{}` as unsigned int", item)))
 }
 
-pub fn as_chars(item: &String) -> Vec<char> {
-    item.chars().collect()
-}
-
-pub fn as_contig_unsigned_bytes(line: &String) -> Vec<u8> {
-    line.chars().map(|n| n as u8 - 48).collect()
+pub fn as_contig_unsigned_bytes(line: &String) -> Result<Vec<u8>, String> {
+    line.chars()
+        .map(|chr| {
+            chr.to_digit(10)
+                .map(|digit| digit as u8)
+                .ok_or(format!("Expected a digit, got `{}`", chr))
+        })
+        .collect()
 }
 
-pub fn as_unsigned_ints_from_line(line: &String) -> Vec<u32> {
-    line.split(',').map(|n| n.parse().unwrap()).collect()
+pub fn as_unsigned_ints_from_line(line: &String) -> Result<Vec<u32>, String> {
+    line.split(',')
+        .map(|n| {
+            n.trim()
+                .parse()
+                .or(Err(format!("Couldn't parse `{}` as unsigned int", n)))
+        })
+        .collect()
 }
@@ -0,0 +1,103 @@
+#[cfg(feature = "mem-stats")]
+pub use counting::measure;
+
+#[cfg(feature = "mem-stats")]
+mod counting {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // Counts every heap operation so each puzzle phase can report what it cost
+    struct CountingAllocator;
+
+    static CURRENT_BYTES: AtomicUsize = AtomicUsize::new(0);
+    static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+    static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+    static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn record_growth(bytes: usize) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(bytes, Ordering::Relaxed);
+        let current = CURRENT_BYTES.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        PEAK_BYTES.fetch_max(current, Ordering::Relaxed);
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                record_growth(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc_zeroed(layout);
+            if !ptr.is_null() {
+                record_growth(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            CURRENT_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = System.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                CURRENT_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+                record_growth(new_size);
+            }
+            new_ptr
+        }
+    }
+
+    fn format_bytes(bytes: usize) -> String {
+        let units = ["B", "KiB", "MiB", "GiB"];
+        let (value, unit) =
+            units[1..]
+                .iter()
+                .fold((bytes as f64, units[0]), |(value, unit), next_unit| {
+                    if value >= 1024f64 {
+                        (value / 1024f64, *next_unit)
+                    } else {
+                        (value, unit)
+                    }
+                });
+        format!("{:.1} {}", value, unit)
+    }
+
+    pub fn measure<T, F: FnOnce() -> T>(phase: &str, run_phase: F) -> T {
+        let start_bytes = CURRENT_BYTES.load(Ordering::Relaxed);
+        let start_allocations = ALLOCATIONS.load(Ordering::Relaxed);
+        let start_allocated = ALLOCATED_BYTES.load(Ordering::Relaxed);
+        PEAK_BYTES.store(start_bytes, Ordering::Relaxed);
+
+        let result = run_phase();
+
+        // Peak is relative to what was already live when the phase started
+        let peak = PEAK_BYTES
+            .load(Ordering::Relaxed)
+            .saturating_sub(start_bytes);
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - start_allocations;
+        let allocated = ALLOCATED_BYTES.load(Ordering::Relaxed) - start_allocated;
+        eprintln!(
+            "[mem] {:<8} peak {:>10}, {:>9} allocations, {:>10} allocated",
+            phase,
+            format_bytes(peak),
+            allocations,
+            format_bytes(allocated)
+        );
+
+        result
+    }
+}
+
+#[cfg(not(feature = "mem-stats"))]
+pub fn measure<T, F: FnOnce() -> T>(_phase: &str, run_phase: F) -> T {
+    run_phase()
+}
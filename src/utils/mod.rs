@@ -1,2 +1,14 @@
 pub mod input_file;
 pub mod hashmap;
+pub mod mem_stats;
+pub mod ppm;
+#[cfg(feature = "fetch")]
+pub mod fetch;
+#[cfg(feature = "submit")]
+pub mod history;
+#[cfg(any(feature = "fetch", feature = "submit"))]
+pub mod prompt;
+#[cfg(any(feature = "fetch", feature = "submit"))]
+pub mod session;
+#[cfg(feature = "submit")]
+pub mod submit;
@@ -0,0 +1,59 @@
+pub type Rgb = (u8, u8, u8);
+
+// Plain PPM spends up to 12 bytes a pixel, so this keeps an image under 50 MB
+pub const MAX_PIXELS: usize = 4_000_000;
+
+// Plain (P3) PPM so images can go to stdout like any other report. Each cell of the grid
+// is drawn as a `scale` pixel square in the colour picked for it.
+pub fn render<T>(
+    grid: &[Vec<T>],
+    scale: usize,
+    colour: impl Fn(&T) -> Rgb,
+) -> Result<String, String> {
+    let (rows, cols) = (grid.len(), grid.first().map_or(0, |row| row.len()));
+    let size = cols.checked_mul(scale).zip(rows.checked_mul(scale));
+    let (width, height) = size
+        .filter(|(width, height)| width.checked_mul(*height).is_some_and(|n| n <= MAX_PIXELS))
+        .ok_or(format!("Images can have at most {} pixels", MAX_PIXELS))?;
+
+    let mut ppm = format!("P3\n{} {}\n255", width, height);
+    for row in grid {
+        let pixels = row
+            .iter()
+            .map(|cell| {
+                let (r, g, b) = colour(cell);
+                vec![format!("{} {} {}", r, g, b); scale].join(" ")
+            })
+            .collect::<Vec<String>>()
+            .join(" ");
+        for _ in 0..scale {
+            ppm += &format!("\n{}", pixels);
+        }
+    }
+
+    Ok(ppm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scales_each_cell_to_a_square() {
+        let grid = vec![vec![0u8, 255]];
+        let ppm = render(&grid, 2, |&value| (value, value, value)).unwrap();
+        assert_eq!(
+            ppm,
+            "P3\n4 2\n255\n0 0 0 0 0 0 255 255 255 255 255 255\n0 0 0 0 0 0 255 255 255 255 255 255"
+        );
+    }
+
+    #[test]
+    fn refuses_oversized_images() {
+        let grid = vec![vec![0u8; 2]; 2];
+        let black = |_: &u8| (0, 0, 0);
+        assert!(render(&grid, usize::MAX, black).is_err());
+        assert!(render(&grid, 1001, black).is_err());
+        assert!(render(&grid, 1000, black).is_ok());
+    }
+}
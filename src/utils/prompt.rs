@@ -0,0 +1,11 @@
+use std::io::{self, BufRead, Write};
+
+// Anything but an explicit `y` counts as no
+pub fn confirm(question: &str) -> bool {
+    print!("{} [y/N] ", question);
+    io::stdout().flush().ok();
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer).ok();
+    answer.trim().eq_ignore_ascii_case("y")
+}
@@ -0,0 +1,62 @@
+use std::env;
+use std::fs;
+
+pub const YEAR: u16 = 2021;
+
+const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+const DEFAULT_SESSION_FILE: &str = ".aoc-session";
+
+// Token comes from AOC_SESSION, else from the file at AOC_SESSION_FILE (or .aoc-session).
+// AOC_BASE_URL lets us point at a local stand-in server instead of the real site.
+const SESSION_VAR: &str = "AOC_SESSION";
+const SESSION_FILE_VAR: &str = "AOC_SESSION_FILE";
+const BASE_URL_VAR: &str = "AOC_BASE_URL";
+
+type SessionErr = String;
+
+pub struct Session {
+    token: String,
+    base_url: String,
+}
+
+impl Session {
+    pub fn load() -> Result<Self, SessionErr> {
+        let token = env::var(SESSION_VAR)
+            .ok()
+            .or_else(read_session_file)
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty())
+            .ok_or(format!(
+                "No session token found: set {} or write it to {}",
+                SESSION_VAR,
+                session_file_path()
+            ))?;
+
+        let base_url = env::var(BASE_URL_VAR).unwrap_or(String::from(DEFAULT_BASE_URL));
+
+        Ok(Self {
+            token,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    pub fn day_url(&self, day: u8) -> String {
+        format!("{}/{}/day/{}", self.base_url, YEAR, day)
+    }
+
+    pub fn cookie(&self) -> String {
+        format!("session={}", self.token)
+    }
+
+    pub fn user_agent(&self) -> String {
+        format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+    }
+}
+
+fn session_file_path() -> String {
+    env::var(SESSION_FILE_VAR).unwrap_or(String::from(DEFAULT_SESSION_FILE))
+}
+
+fn read_session_file() -> Option<String> {
+    fs::read_to_string(session_file_path()).ok()
+}
@@ -0,0 +1,242 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+use crate::utils::history::{Attempt, History};
+use crate::utils::prompt;
+use crate::utils::session::Session;
+
+type SubmitErr = String;
+
+#[derive(Clone, Copy)]
+pub enum Outcome {
+    Correct,
+    TooHigh,
+    TooLow,
+    Incorrect,
+    RateLimited,
+    WrongLevel,
+    Unknown,
+}
+
+impl Outcome {
+    pub fn from_response(body: &str) -> Self {
+        if body.contains("That's the right answer") {
+            Outcome::Correct
+        } else if body.contains("You gave an answer too recently") {
+            Outcome::RateLimited
+        } else if body.contains("your answer is too high") {
+            Outcome::TooHigh
+        } else if body.contains("your answer is too low") {
+            Outcome::TooLow
+        } else if body.contains("That's not the right answer") {
+            Outcome::Incorrect
+        } else if body.contains("You don't seem to be solving the right level") {
+            Outcome::WrongLevel
+        } else {
+            Outcome::Unknown
+        }
+    }
+
+    // Whether the server actually judged the answer (as opposed to refusing to look at it)
+    pub fn is_verdict(&self) -> bool {
+        matches!(
+            self,
+            Outcome::Correct | Outcome::TooHigh | Outcome::TooLow | Outcome::Incorrect
+        )
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(match self {
+            Outcome::Correct => "correct",
+            Outcome::TooHigh => "too-high",
+            Outcome::TooLow => "too-low",
+            Outcome::Incorrect => "incorrect",
+            Outcome::RateLimited => "rate-limited",
+            Outcome::WrongLevel => "wrong-level",
+            Outcome::Unknown => "unknown",
+        })
+    }
+}
+
+impl FromStr for Outcome {
+    type Err = SubmitErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "correct" => Ok(Outcome::Correct),
+            "too-high" => Ok(Outcome::TooHigh),
+            "too-low" => Ok(Outcome::TooLow),
+            "incorrect" => Ok(Outcome::Incorrect),
+            "rate-limited" => Ok(Outcome::RateLimited),
+            "wrong-level" => Ok(Outcome::WrongLevel),
+            "unknown" => Ok(Outcome::Unknown),
+            _ => Err(format!("Unknown outcome: `{}`", s)),
+        }
+    }
+}
+
+fn post_answer(day: u8, part: u8, answer: &str, session: &Session) -> Result<Outcome, SubmitErr> {
+    let url = format!("{}/answer", session.day_url(day));
+    let level = part.to_string();
+    let body = ureq::post(&url)
+        .set("Cookie", &session.cookie())
+        .set("User-Agent", &session.user_agent())
+        .send_form(&[("level", level.as_str()), ("answer", answer)])
+        .map_err(|err| format!("Couldn't submit answer for puzzle {}: {}", day, err))?
+        .into_string()
+        .map_err(|err| format!("Couldn't read response for puzzle {}: {}", day, err))?;
+
+    Ok(Outcome::from_response(&body))
+}
+
+// Answers the server has already judged never need to be sent again
+fn check_history(history: &History, day: u8, part: u8, answer: &str) -> Result<(), SubmitErr> {
+    if let Some(solved) = history.solved(day, part) {
+        return Err(format!(
+            "Puzzle {} part {} was already solved with {}",
+            day, part, solved.answer
+        ));
+    }
+
+    if let Some(previous) = history.find(day, part, answer) {
+        return Err(format!(
+            "Refusing to resubmit {} for puzzle {} part {}: it was {}",
+            answer, day, part, previous.outcome
+        ));
+    }
+
+    Ok(())
+}
+
+fn out_of_bounds_warning(history: &History, day: u8, part: u8, answer: &str) -> Option<String> {
+    let value = answer.parse::<i64>().ok()?;
+    let bounds = history.bounds(day, part);
+
+    match (bounds.too_low, bounds.too_high) {
+        (Some(low), _) if value <= low => Some(format!(
+            "{} is not above {}, which was already too low.",
+            value, low
+        )),
+        (_, Some(high)) if value >= high => Some(format!(
+            "{} is not below {}, which was already too high.",
+            value, high
+        )),
+        _ => None,
+    }
+}
+
+pub fn submit(day: u8, part: u8, answer: &str) -> Result<Outcome, SubmitErr> {
+    if answer.is_empty() || answer.contains(char::is_whitespace) {
+        return Err(format!(
+            "Answer for puzzle {} part {} can't be submitted as-is:\n{}",
+            day, part, answer
+        ));
+    }
+
+    let mut history = History::load()?;
+    check_history(&history, day, part, answer)?;
+
+    if let Some(warning) = out_of_bounds_warning(&history, day, part, answer) {
+        if !prompt::confirm(&format!("{} Submit anyway?", warning)) {
+            return Err(String::from("Submission cancelled"));
+        }
+    }
+
+    let session = Session::load()?;
+    let outcome = post_answer(day, part, answer, &session)?;
+    history.record(Attempt {
+        day,
+        part,
+        answer: String::from(answer),
+        outcome,
+    })?;
+
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attempt(part: u8, answer: &str, outcome: Outcome) -> Attempt {
+        Attempt {
+            day: 1,
+            part,
+            answer: String::from(answer),
+            outcome,
+        }
+    }
+
+    #[test]
+    fn recognises_each_response() {
+        let responses = [
+            (
+                "<p>That's the right answer!  You are one gold star closer",
+                "correct",
+            ),
+            (
+                "<p>That's not the right answer; your answer is too high.",
+                "too-high",
+            ),
+            (
+                "<p>That's not the right answer; your answer is too low.",
+                "too-low",
+            ),
+            (
+                "<p>That's not the right answer.  If you're stuck",
+                "incorrect",
+            ),
+            (
+                "<p>You gave an answer too recently; you have to wait",
+                "rate-limited",
+            ),
+            (
+                "<p>You don't seem to be solving the right level.",
+                "wrong-level",
+            ),
+            ("<html>Something else entirely</html>", "unknown"),
+        ];
+
+        for (body, outcome) in responses {
+            assert_eq!(Outcome::from_response(body).to_string(), outcome);
+        }
+    }
+
+    #[test]
+    fn only_verdicts_are_final() {
+        let history = History::with_attempts(vec![
+            attempt(1, "100", Outcome::TooLow),
+            attempt(1, "200", Outcome::RateLimited),
+            attempt(2, "300", Outcome::Correct),
+        ]);
+
+        assert_eq!(
+            check_history(&history, 1, 1, "100"),
+            Err(String::from(
+                "Refusing to resubmit 100 for puzzle 1 part 1: it was too-low"
+            ))
+        );
+        assert_eq!(check_history(&history, 1, 1, "200"), Ok(()));
+        assert_eq!(check_history(&history, 1, 1, "150"), Ok(()));
+        assert_eq!(
+            check_history(&history, 1, 2, "400"),
+            Err(String::from("Puzzle 1 part 2 was already solved with 300"))
+        );
+    }
+
+    #[test]
+    fn warns_outside_known_bounds() {
+        let history = History::with_attempts(vec![
+            attempt(1, "100", Outcome::TooLow),
+            attempt(1, "500", Outcome::TooHigh),
+        ]);
+
+        assert!(out_of_bounds_warning(&history, 1, 1, "100").is_some());
+        assert!(out_of_bounds_warning(&history, 1, 1, "600").is_some());
+        assert!(out_of_bounds_warning(&history, 1, 1, "300").is_none());
+        assert!(out_of_bounds_warning(&history, 1, 1, "abc").is_none());
+        assert!(out_of_bounds_warning(&history, 1, 2, "100").is_none());
+    }
+}
@@ -0,0 +1,194 @@
+use std::env;
+use std::fs;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::puzzles;
+use crate::puzzles::puzzle::Answers;
+use crate::utils::input_file;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+// Features the running binary was built with, so a rebuild doesn't silently drop them
+const ENABLED_FEATURES: [(&str, bool); 3] = [
+    ("fetch", cfg!(feature = "fetch")),
+    ("submit", cfg!(feature = "submit")),
+    ("mem-stats", cfg!(feature = "mem-stats")),
+];
+
+type WatchErr = String;
+type Snapshot = Vec<Option<SystemTime>>;
+
+struct Watcher {
+    day: u8,
+    test_input: bool,
+    rebuild: bool,
+    paths: Vec<PathBuf>,
+    // Resolved up front: once a rebuild replaces the binary, current_exe points at the deleted file
+    exe: PathBuf,
+}
+
+impl Watcher {
+    pub fn new(day: u8, test_input: bool, rebuild: bool) -> Result<Self, WatchErr> {
+        let exe = env::current_exe().map_err(|err| err.to_string())?;
+        let mut paths = vec![input_file::input_file_path(day, test_input)];
+        if rebuild {
+            paths.extend(source_files(day));
+        }
+
+        Ok(Self {
+            day,
+            test_input,
+            rebuild,
+            paths,
+            exe,
+        })
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        self.paths
+            .iter()
+            .map(|path| fs::metadata(path).and_then(|meta| meta.modified()).ok())
+            .collect()
+    }
+
+    fn compute_answers(&self) -> Result<Answers, WatchErr> {
+        if self.rebuild {
+            rebuild(&self.exe)?;
+            self.run_rebuilt()
+        } else {
+            let (day, test_input) = (self.day, self.test_input);
+            panic::catch_unwind(|| puzzles::answers(day, test_input))
+                .map_err(|_| format!("Puzzle {} panicked", day))?
+        }
+    }
+
+    // The freshly built binary prints part one on the first line, part two on the rest
+    fn run_rebuilt(&self) -> Result<Answers, WatchErr> {
+        let mut args = vec![
+            String::from("run"),
+            String::from("--day"),
+            self.day.to_string(),
+        ];
+        if self.test_input {
+            args.push(String::from("--test"));
+        }
+
+        let output = Command::new(&self.exe)
+            .args(&args)
+            .output()
+            .map_err(|err| format!("Couldn't run puzzle {}: {}", self.day, err))?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let (part_one, part_two) = stdout.trim_end().split_once('\n').unwrap_or((&stdout, ""));
+        Ok(Answers {
+            part_one: part_one.to_string(),
+            part_two: part_two.to_string(),
+        })
+    }
+}
+
+fn source_files(day: u8) -> Vec<PathBuf> {
+    let dir = PathBuf::from(format!("src/puzzles/p{}", day));
+    fs::read_dir(&dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Cargo puts each profile's binaries in target/<dir>, where the dev profile's dir is `debug`
+fn build_profile(exe: &Path) -> Result<String, WatchErr> {
+    let dir = exe
+        .parent()
+        .and_then(|dir| dir.file_name())
+        .and_then(|name| name.to_str())
+        .ok_or(format!(
+            "Couldn't tell which profile built \"{}\"",
+            exe.display()
+        ))?;
+
+    Ok(match dir {
+        "debug" => String::from("dev"),
+        profile => profile.to_string(),
+    })
+}
+
+// Rebuilds the binary in place, with the same profile and features as the one running
+fn rebuild(exe: &Path) -> Result<(), WatchErr> {
+    let mut args = vec![
+        String::from("build"),
+        String::from("--quiet"),
+        String::from("--profile"),
+        build_profile(exe)?,
+    ];
+    let features: Vec<&str> = ENABLED_FEATURES
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(feature, _)| *feature)
+        .collect();
+    if !features.is_empty() {
+        args.push(String::from("--features"));
+        args.push(features.join(","));
+    }
+
+    let status = Command::new("cargo")
+        .args(&args)
+        .status()
+        .map_err(|err| format!("Couldn't rebuild: {}", err))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(String::from("Rebuild failed"))
+    }
+}
+
+fn print_diff(label: &str, previous: Option<&String>, current: &String) {
+    match previous {
+        Some(previous) if previous == current => println!("{}: {} (unchanged)", label, current),
+        Some(previous) => println!("{}: {} -> {}", label, previous, current),
+        None => println!("{}: {}", label, current),
+    }
+}
+
+fn report(previous: &Option<Answers>, current: &Result<Answers, WatchErr>) {
+    match current {
+        Ok(answers) => {
+            let previous = previous.as_ref();
+            print_diff("Part one", previous.map(|a| &a.part_one), &answers.part_one);
+            print_diff("Part two", previous.map(|a| &a.part_two), &answers.part_two);
+        }
+        Err(err) => eprintln!("{}", err),
+    }
+}
+
+pub fn watch(day: u8, test_input: bool, rebuild: bool) -> Result<(), WatchErr> {
+    let watcher = Watcher::new(day, test_input, rebuild)?;
+    for path in &watcher.paths {
+        println!("Watching \"{}\"", path.display());
+    }
+
+    let mut last_snapshot = watcher.snapshot();
+    let mut last_answers = None;
+    loop {
+        let answers = watcher.compute_answers();
+        report(&last_answers, &answers);
+        if let Ok(answers) = answers {
+            last_answers = Some(answers);
+        }
+
+        while watcher.snapshot() == last_snapshot {
+            thread::sleep(POLL_INTERVAL);
+        }
+        last_snapshot = watcher.snapshot();
+        println!();
+    }
+}
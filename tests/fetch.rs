@@ -0,0 +1,121 @@
+#![cfg(feature = "fetch")]
+
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Output};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const INPUT: &str = "199\n200\n208\n";
+
+// Serves day 1's input and a 404 for every other day, recording each request line and cookie
+fn stub_server() -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+
+    let seen = Arc::clone(&requests);
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(&stream);
+            let mut head = Vec::new();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                    break;
+                }
+                head.push(line.trim_end().to_string());
+            }
+
+            let request = head.first().cloned().unwrap_or_default();
+            let cookie = head
+                .iter()
+                .find(|line| line.to_ascii_lowercase().starts_with("cookie:"))
+                .cloned()
+                .unwrap_or_default();
+            seen.lock()
+                .unwrap()
+                .push(format!("{} | {}", request, cookie));
+
+            let (status, body) = if request.starts_with("GET /2021/day/1/input ") {
+                ("200 OK", INPUT)
+            } else {
+                ("404 Not Found", "Not found")
+            };
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+        }
+    });
+
+    (base_url, requests)
+}
+
+fn work_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("aoc-fetch-{}-{}", process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn fetch(dir: &Path, base_url: &str, day: u8) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_aoc2021-rust"))
+        .args(["fetch", "--day", &day.to_string()])
+        .current_dir(dir)
+        .env("AOC_SESSION", "stub-token")
+        .env("AOC_BASE_URL", base_url)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn fetches_into_input_files_and_reuses_the_copy() {
+    let (base_url, requests) = stub_server();
+    let dir = work_dir("cache");
+
+    let output = fetch(&dir, &base_url, 1);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "input-files/1.txt"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("input-files/1.txt")).unwrap(),
+        INPUT
+    );
+    assert_eq!(
+        *requests.lock().unwrap(),
+        ["GET /2021/day/1/input HTTP/1.1 | Cookie: session=stub-token"]
+    );
+
+    let output = fetch(&dir, &base_url, 1);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(requests.lock().unwrap().len(), 1);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn http_errors_leave_nothing_behind() {
+    let (base_url, requests) = stub_server();
+    let dir = work_dir("error");
+
+    let output = fetch(&dir, &base_url, 2);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).starts_with("Couldn't fetch input for puzzle 2: ")
+    );
+    assert!(!dir.join("input-files/2.txt").exists());
+    assert_eq!(requests.lock().unwrap().len(), 1);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
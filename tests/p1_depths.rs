@@ -0,0 +1,28 @@
+use aoc2021_rust::puzzles::p1::analyze_depths;
+
+const DEPTHS: [u32; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+
+#[test]
+fn counts_changes_for_any_window_size() {
+    let single = analyze_depths(&DEPTHS, 1);
+    assert_eq!(single.changes().increases(), 7);
+    assert_eq!(single.changes().decreases(), 2);
+
+    let triple = analyze_depths(&DEPTHS, 3);
+    assert_eq!(triple.window_size(), 3);
+    assert_eq!(triple.windows().len(), 8);
+    assert_eq!(triple.changes().increases(), 5);
+    assert_eq!(triple.changes().plateaus(), 1);
+    assert_eq!(triple.changes().longest_increasing_run(), 4);
+}
+
+#[test]
+fn tracks_each_window() {
+    let analysis = analyze_depths(&DEPTHS, 4);
+    let first = &analysis.windows()[0];
+    assert_eq!((first.sum(), first.min(), first.max()), (817, 199, 210));
+    assert_eq!(first.mean(), 204.25);
+
+    assert!(analyze_depths(&DEPTHS, 11).windows().is_empty());
+    assert!(analyze_depths(&DEPTHS, 0).windows().is_empty());
+}
@@ -0,0 +1,73 @@
+use aoc2021_rust::puzzles::p2::{Command, Interpreter, P2};
+use aoc2021_rust::puzzles::puzzle::Puzzle;
+
+fn commands(lines: &[&str]) -> Vec<Command> {
+    let raw_data = lines.iter().map(|line| line.to_string()).collect();
+    P2.parse_data(&raw_data).unwrap()
+}
+
+#[test]
+fn custom_verbs_can_read_and_move_the_submarine() {
+    let interpreter = Interpreter::with_aim()
+        .with("dive", |sub, value| {
+            let depth = sub.depth();
+            sub.change_depth(depth * (value - 1))
+        })
+        .with("mark", |sub, _| {
+            let position = sub.position();
+            sub.set_register("mark", position);
+            Ok(())
+        });
+
+    let trace = interpreter
+        .trace(&[
+            Command::new("down", 2),
+            Command::new("forward", 3),
+            Command::new("mark", 0),
+            Command::new("dive", 3),
+        ])
+        .unwrap();
+    let submarine = trace.steps().last().unwrap();
+    assert_eq!(
+        (submarine.position(), submarine.depth(), submarine.aim()),
+        (3, 18, 2)
+    );
+    assert_eq!(submarine.register("mark"), 3);
+    assert_eq!(submarine.register("unset"), 0);
+}
+
+#[test]
+fn manoeuvres_reverse_forward_and_keep_a_heading() {
+    let commands = commands(&["down 2", "forward 5", "back 2", "turn 90", "turn -180"]);
+
+    let submarine = Interpreter::with_aim()
+        .with_manoeuvres()
+        .run(&commands)
+        .unwrap();
+    assert_eq!((submarine.position(), submarine.depth()), (3, 6));
+    assert_eq!(submarine.register("heading"), 270);
+
+    let submarine = Interpreter::simple()
+        .with_manoeuvres()
+        .run(&commands)
+        .unwrap();
+    assert_eq!((submarine.position(), submarine.depth()), (3, 2));
+}
+
+#[test]
+fn unknown_verbs_are_errors() {
+    let result = Interpreter::simple().run(&commands(&["back 1"]));
+    assert_eq!(result.err(), Some(String::from("Unknown command: `back`")));
+}
+
+#[test]
+fn overflow_is_a_solver_error() {
+    let aimed_too_deep = commands(&["down 5", "forward 9223372036854775807"]);
+    assert!(P2.solve_part_two(&aimed_too_deep).is_err());
+
+    let product_too_large = commands(&["forward 99999999999", "down 99999999999"]);
+    assert!(P2.solve_part_one(&product_too_large).is_err());
+
+    let negated_minimum = commands(&["up -9223372036854775808"]);
+    assert!(P2.solve_part_one(&negated_minimum).is_err());
+}
@@ -0,0 +1,65 @@
+use aoc2021_rust::puzzles::p7::{
+    align, align_convex, align_scan, Capped, FuelCost, Linear, Triangular, Weighted,
+};
+
+// Every step costs the same as the crab's index plus one, as an outside cost might
+struct PerCrab;
+impl FuelCost for PerCrab {
+    fn fuel_for(&self, crab: usize, delta: u64) -> Option<u64> {
+        delta.checked_mul(crab as u64 + 1)
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+// Claims the optimum is at one of the crabs, which holds for any linear cost
+struct AtACrab;
+impl FuelCost for AtACrab {
+    fn fuel_for(&self, _crab: usize, delta: u64) -> Option<u64> {
+        Some(delta)
+    }
+
+    fn closed_form(&self, positions: &[u32]) -> Option<Vec<u32>> {
+        Some(positions.to_vec())
+    }
+}
+
+const POSITIONS: [u32; 10] = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+
+#[test]
+fn outside_costs_use_every_solver() {
+    let searched = align_convex(&POSITIONS, &PerCrab).unwrap();
+    let scanned = align_scan(&POSITIONS, &PerCrab).unwrap();
+    assert_eq!(
+        (searched.position(), searched.fuel()),
+        (scanned.position(), scanned.fuel())
+    );
+
+    let closed = align(&POSITIONS, &AtACrab).unwrap();
+    assert_eq!((closed.position(), closed.fuel()), (2, 37));
+}
+
+#[test]
+fn wrapped_costs_can_be_built_outside() {
+    let weighted = Weighted::new(Box::new(Linear), vec![2; POSITIONS.len()]);
+    assert_eq!(align(&POSITIONS, &weighted).unwrap().fuel(), 74);
+
+    let capped = Capped::new(Box::new(Triangular), 10);
+    let alignment = align(&POSITIONS, &capped).unwrap();
+    let scanned = align_scan(&POSITIONS, &capped).unwrap();
+    assert_eq!(alignment.fuel(), scanned.fuel());
+}
+
+#[test]
+fn overflow_is_an_error() {
+    let mut positions = vec![0; 5];
+    positions.extend([4_000_000_000; 5]);
+    assert!(align(&positions, &Triangular).is_err());
+
+    let weighted = Weighted::new(Box::new(Linear), vec![u64::MAX; 2]);
+    assert!(align(&[0, 2], &weighted).is_err());
+
+    assert!(align(&[], &Linear).is_err());
+}
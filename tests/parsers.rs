@@ -0,0 +1,327 @@
+use proptest::prelude::*;
+use proptest::sample::Index;
+
+use aoc2021_rust::puzzles::puzzle::Puzzle;
+use aoc2021_rust::puzzles::{
+    p1::P1, p10::P10, p11::P11, p12::P12, p13::P13, p14::P14, p15::P15, p16::P16, p17::P17, p2::P2,
+    p3::P3, p4::P4, p5::P5, p6::P6, p7::P7, p8::P8, p9::P9,
+};
+
+type Lines = Vec<String>;
+
+fn arbitrary_lines() -> impl Strategy<Value = Lines> {
+    prop::collection::vec(".*", 0..6)
+}
+
+// Characters the puzzle formats are built from, so mutations land near the parsers' edge cases
+fn mutation_char() -> impl Strategy<Value = char> {
+    prop_oneof![
+        any::<char>(),
+        prop::sample::select(vec!['0', '9', '-', ',', ' ', '|', '>', '\n', 'a', 'g', 'F']),
+    ]
+}
+
+// Truncates, deletes or inserts one character somewhere in an otherwise valid input
+fn mutated(valid: impl Strategy<Value = Lines>) -> impl Strategy<Value = Lines> {
+    (valid, any::<Index>(), 0..3u8, mutation_char()).prop_map(|(lines, index, mutation, chr)| {
+        let mut chars = lines.join("\n").chars().collect::<Vec<char>>();
+        let at = index.index(chars.len() + 1);
+        match mutation {
+            0 => chars.truncate(at),
+            1 if at < chars.len() => {
+                chars.remove(at);
+            }
+            _ => chars.insert(at, chr),
+        }
+
+        let input = chars.into_iter().collect::<String>();
+        input.split('\n').map(String::from).collect()
+    })
+}
+
+fn comma_separated<T: ToString>(values: Vec<T>) -> String {
+    values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+// Rows of `cols` characters, each one of the given alphabet
+fn fixed_width_rows(
+    alphabet: &'static str,
+    rows: usize,
+    cols: usize,
+) -> impl Strategy<Value = Lines> {
+    let chars = alphabet.chars().collect::<Vec<char>>();
+    let row = prop::collection::vec(prop::sample::select(chars), cols)
+        .prop_map(|row| row.into_iter().collect::<String>());
+    prop::collection::vec(row, rows)
+}
+
+fn digit_grid() -> impl Strategy<Value = Lines> {
+    (1..12usize, 1..12usize)
+        .prop_flat_map(|(rows, cols)| fixed_width_rows("0123456789", rows, cols))
+}
+
+fn depths() -> impl Strategy<Value = Lines> {
+    prop::collection::vec(any::<u32>().prop_map(|depth| depth.to_string()), 0..20)
+}
+
+fn commands() -> impl Strategy<Value = Lines> {
+    prop::collection::vec("(forward|down|up) [0-9]{1,3}", 0..20)
+}
+
+fn diagnostic_report() -> impl Strategy<Value = Lines> {
+    (1..=64usize, 1..20usize).prop_flat_map(|(width, lines)| fixed_width_rows("01", lines, width))
+}
+
+fn bingo_subsystem() -> impl Strategy<Value = Lines> {
+    let draw_order = prop::collection::vec(0..100u32, 1..30).prop_map(comma_separated);
+    let boards = (1..6usize).prop_flat_map(|size| {
+        let row = prop::collection::vec(0..100u32, size).prop_map(|row| {
+            row.iter()
+                .map(|num| format!("{:>2}", num))
+                .collect::<Vec<String>>()
+                .join(" ")
+        });
+        prop::collection::vec(prop::collection::vec(row, size), 0..4)
+    });
+
+    (draw_order, boards).prop_map(|(draw_order, boards)| {
+        let mut lines = vec![draw_order];
+        for board in boards {
+            lines.push(String::new());
+            lines.extend(board);
+        }
+        lines
+    })
+}
+
+fn line_segments() -> impl Strategy<Value = Lines> {
+    let coordinate = -1000..1000i32;
+    let segment = (
+        coordinate.clone(),
+        coordinate.clone(),
+        coordinate.clone(),
+        coordinate,
+    )
+        .prop_map(|(x0, y0, x1, y1)| format!("{},{} -> {},{}", x0, y0, x1, y1));
+    prop::collection::vec(segment, 0..20)
+}
+
+fn timers() -> impl Strategy<Value = Lines> {
+    prop::collection::vec(0..9usize, 1..30).prop_map(|timers| vec![comma_separated(timers)])
+}
+
+fn crab_positions() -> impl Strategy<Value = Lines> {
+    prop::collection::vec(any::<u32>(), 1..30)
+        .prop_map(|positions| vec![comma_separated(positions)])
+}
+
+fn notes() -> impl Strategy<Value = Lines> {
+    let entry = (
+        prop::collection::vec("[a-g]{1,7}", 10),
+        prop::collection::vec("[a-g]{1,7}", 1..6),
+    )
+        .prop_map(|(patterns, output)| format!("{} | {}", patterns.join(" "), output.join(" ")));
+    prop::collection::vec(entry, 0..10)
+}
+
+fn navigation_subsystem() -> impl Strategy<Value = Lines> {
+    prop::collection::vec(r"[\[\](){}<>]{0,30}", 0..20)
+}
+
+fn cave_connections() -> impl Strategy<Value = Lines> {
+    prop::collection::vec(
+        "(start|end|[a-z]{1,3}|[A-Z]{1,3})-(start|end|[a-z]{1,3}|[A-Z]{1,3})",
+        0..20,
+    )
+}
+
+fn origami_instructions() -> impl Strategy<Value = Lines> {
+    let dots = prop::collection::vec("[0-9]{1,4},[0-9]{1,4}", 0..20);
+    let folds = prop::collection::vec("fold along [xy]=[0-9]{1,4}", 0..5);
+    (dots, folds).prop_map(|(mut lines, folds)| {
+        lines.push(String::new());
+        lines.extend(folds);
+        lines
+    })
+}
+
+fn polymer_manual() -> impl Strategy<Value = Lines> {
+    (
+        "[A-Z]{2,12}",
+        prop::collection::vec("[A-Z]{2} -> [A-Z]", 0..20),
+    )
+        .prop_map(|(template, rules)| {
+            let mut lines = vec![template, String::new()];
+            lines.extend(rules);
+            lines
+        })
+}
+
+#[derive(Debug, Clone)]
+enum Packet {
+    Literal {
+        version: u64,
+        value: u64,
+    },
+    Operator {
+        version: u64,
+        type_id: u64,
+        counted: bool,
+        subpackets: Vec<Packet>,
+    },
+}
+
+fn push_bits(bits: &mut String, value: u64, width: usize) {
+    for shift in (0..width).rev() {
+        bits.push(if (value >> shift) & 1 == 1 { '1' } else { '0' });
+    }
+}
+
+fn encode_packet(packet: &Packet, bits: &mut String) {
+    match packet {
+        Packet::Literal { version, value } => {
+            push_bits(bits, *version, 3);
+            push_bits(bits, 4, 3);
+
+            let groups = (1..=16)
+                .rev()
+                .find(|groups| value >> (4 * (groups - 1)) != 0);
+            let groups = groups.unwrap_or(1);
+            for group in (0..groups).rev() {
+                push_bits(bits, u64::from(group > 0), 1);
+                push_bits(bits, (value >> (4 * group)) & 0xF, 4);
+            }
+        }
+        Packet::Operator {
+            version,
+            type_id,
+            counted,
+            subpackets,
+        } => {
+            push_bits(bits, *version, 3);
+            push_bits(bits, *type_id, 3);
+
+            let mut subpacket_bits = String::new();
+            for subpacket in subpackets {
+                encode_packet(subpacket, &mut subpacket_bits);
+            }
+
+            if *counted {
+                push_bits(bits, 1, 1);
+                push_bits(bits, subpackets.len() as u64, 11);
+            } else {
+                push_bits(bits, 0, 1);
+                push_bits(bits, subpacket_bits.len() as u64, 15);
+            }
+            bits.push_str(&subpacket_bits);
+        }
+    }
+}
+
+fn packet() -> impl Strategy<Value = Packet> {
+    let literal =
+        (0..8u64, any::<u64>()).prop_map(|(version, value)| Packet::Literal { version, value });
+    literal.prop_recursive(3, 24, 4, |inner| {
+        let operator = |type_ids: Vec<u64>, subpackets: BoxedStrategy<Vec<Packet>>| {
+            (
+                0..8u64,
+                prop::sample::select(type_ids),
+                any::<bool>(),
+                subpackets,
+            )
+                .prop_map(|(version, type_id, counted, subpackets)| Packet::Operator {
+                    version,
+                    type_id,
+                    counted,
+                    subpackets,
+                })
+        };
+
+        prop_oneof![
+            operator(
+                vec![0, 1, 2, 3],
+                prop::collection::vec(inner.clone(), 1..4).boxed()
+            ),
+            operator(vec![5, 6, 7], prop::collection::vec(inner, 2).boxed()),
+        ]
+    })
+}
+
+fn transmission() -> impl Strategy<Value = Lines> {
+    packet().prop_map(|packet| {
+        let mut bits = String::new();
+        encode_packet(&packet, &mut bits);
+        while bits.len() % 4 != 0 {
+            bits.push('0');
+        }
+
+        let hex = bits
+            .as_bytes()
+            .chunks(4)
+            .map(|nibble| {
+                let value = nibble
+                    .iter()
+                    .fold(0, |value, bit| (value << 1) | u32::from(bit - b'0'));
+                std::char::from_digit(value, 16)
+                    .unwrap()
+                    .to_ascii_uppercase()
+            })
+            .collect::<String>();
+        vec![hex]
+    })
+}
+
+fn target_area() -> impl Strategy<Value = Lines> {
+    (any::<i32>(), any::<i32>(), any::<i32>(), any::<i32>()).prop_map(|(x0, x1, y0, y1)| {
+        vec![format!("target area: x={}..{}, y={}..{}", x0, x1, y0, y1)]
+    })
+}
+
+// Well-formed input must parse, and no input at all may make a parser panic
+macro_rules! parser_properties {
+    ($day:ident, $puzzle:expr, $valid:expr) => {
+        mod $day {
+            use super::*;
+
+            proptest! {
+                #[test]
+                fn parses_valid_input(raw_data in $valid) {
+                    let parsed = $puzzle.parse_data(&raw_data);
+                    prop_assert!(parsed.is_ok(), "{:?}: {:?}", raw_data, parsed.err());
+                }
+
+                #[test]
+                fn survives_mutated_input(raw_data in mutated($valid)) {
+                    let _ = $puzzle.parse_data(&raw_data);
+                }
+
+                #[test]
+                fn survives_arbitrary_input(raw_data in arbitrary_lines()) {
+                    let _ = $puzzle.parse_data(&raw_data);
+                }
+            }
+        }
+    };
+}
+
+parser_properties!(p1, P1, depths());
+parser_properties!(p2, P2, commands());
+parser_properties!(p3, P3, diagnostic_report());
+parser_properties!(p4, P4, bingo_subsystem());
+parser_properties!(p5, P5, line_segments());
+parser_properties!(p6, P6, timers());
+parser_properties!(p7, P7, crab_positions());
+parser_properties!(p8, P8, notes());
+parser_properties!(p9, P9, digit_grid());
+parser_properties!(p10, P10, navigation_subsystem());
+parser_properties!(p11, P11, digit_grid());
+parser_properties!(p12, P12, cave_connections());
+parser_properties!(p13, P13, origami_instructions());
+parser_properties!(p14, P14, polymer_manual());
+parser_properties!(p15, P15, digit_grid());
+parser_properties!(p16, P16, transmission());
+parser_properties!(p17, P17, target_area());